@@ -0,0 +1,67 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use zognorp::{
+    dlx::solve_dlx,
+    io::load_sdm,
+    puzzle::Puzzle,
+    solver::{BranchStrategy, CellSelection, solve_sudoku, solve_with_strategy},
+};
+
+/// 17 clue puzzles, the fewest clues a sudoku with a unique solution can have
+fn hard_puzzles() -> Vec<Puzzle> {
+    match load_sdm(include_str!("hard17.sdm")) {
+        Ok(puzzles) => puzzles,
+        Err(e) => panic!("couldn't load the benchmark puzzles: {}", e),
+    }
+}
+
+fn bench_solvers(c: &mut Criterion) {
+    let puzzles = hard_puzzles();
+
+    c.bench_function("solve_sudoku hard17", |b| {
+        b.iter(|| {
+            for puzzle in &puzzles {
+                if let Err(e) = solve_sudoku(puzzle) {
+                    panic!("failed to solve {}: {}", puzzle.to_line(), e);
+                }
+            }
+        })
+    });
+
+    c.bench_function("solve_dlx hard17", |b| {
+        b.iter(|| {
+            for puzzle in &puzzles {
+                if solve_dlx(puzzle).is_none() {
+                    panic!("failed to solve {}", puzzle.to_line());
+                }
+            }
+        })
+    });
+}
+
+/// The default most constrained cell selection against `MostCompleteUnit`
+fn bench_cell_selection(c: &mut Criterion) {
+    let puzzles = hard_puzzles();
+
+    for (name, cells) in [
+        ("most constrained", CellSelection::MostConstrained),
+        ("most complete unit", CellSelection::MostCompleteUnit),
+    ] {
+        let strategy = BranchStrategy {
+            cells,
+            ..BranchStrategy::default()
+        };
+
+        c.bench_function(&format!("{} hard17", name), |b| {
+            b.iter(|| {
+                for puzzle in &puzzles {
+                    if let Err(e) = solve_with_strategy(puzzle, strategy) {
+                        panic!("failed to solve {}: {}", puzzle.to_line(), e);
+                    }
+                }
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_solvers, bench_cell_selection);
+criterion_main!(benches);
@@ -0,0 +1,312 @@
+//! Convenience functions that take and return plain strings, for scripts and for
+//! callers on the other side of an FFI or WebAssembly boundary.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use crate::{
+    puzzle::Puzzle,
+    solver::{SolveError, solve_sudoku},
+};
+
+/// Solve a puzzle written as a line of 81 characters, returning the solved line
+pub fn solve_line(input: &str) -> Result<String, String> {
+    let puzzle: Puzzle = input.parse().map_err(|e| format!("{}", e))?;
+
+    solve_sudoku(&puzzle)
+        .map(|solved| solved.to_line())
+        .map_err(|e| format!("{}", e))
+}
+
+/// Solve a puzzle written as a line of 81 characters, returning the solved board
+/// formatted as a grid (see the `Display` impl for `Puzzle`)
+pub fn solve_to_string(input: &str) -> Result<String, String> {
+    let puzzle: Puzzle = input.parse().map_err(|e| format!("{}", e))?;
+
+    solve_sudoku(&puzzle)
+        .map(|solved| solved.to_string())
+        .map_err(|e| format!("{}", e))
+}
+
+/// Solve every puzzle in a stream with one puzzle of 81 characters per line, like an
+/// `.sdm` file. Lines are read and solved one at a time as the iterator is advanced, so
+/// the whole stream never has to fit in memory. Blank lines are skipped, and every other
+/// line gives either its solved line or an error. Lines that can't be read or parsed
+/// give `SolveError::Io` or `SolveError::Parse`.
+pub fn solve_lines<R: BufRead>(reader: R) -> impl Iterator<Item = Result<String, SolveError>> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .map(|line| {
+            let puzzle: Puzzle = line?.parse()?;
+            solve_sudoku(&puzzle).map(|solved| solved.to_line())
+        })
+}
+
+/// Write `s` as a JSON string, with quotes around it
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Write one JSON Lines record for `puzzle` and the result of solving it
+fn write_record<W: Write>(
+    out: &mut W,
+    puzzle: &Puzzle,
+    result: Result<Puzzle, SolveError>,
+) -> io::Result<()> {
+    let (solution, error) = match result {
+        Ok(solved) => (json_string(&solved.to_line()), "null".to_string()),
+        Err(e) => ("null".to_string(), json_string(&e.to_string())),
+    };
+    writeln!(
+        out,
+        "{{\"input\":{},\"solution\":{},\"error\":{}}}",
+        json_string(&puzzle.to_line()),
+        solution,
+        error
+    )
+}
+
+/// Solve many puzzles on every available thread and write the results as JSON Lines,
+/// one object per puzzle in the same order as `puzzles`. Each object has the puzzle's
+/// `input` line, its `solution` line, and an `error` message, with `null` for whichever
+/// of the last two doesn't apply. Each line is written as soon as its puzzle and every
+/// puzzle before it have been solved, so output starts before the whole batch is done.
+pub fn solve_batch_jsonl<W: Write>(puzzles: &[Puzzle], mut out: W) -> io::Result<()> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let (next, sender) = (&next, sender.clone());
+
+            scope.spawn(move || {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(puzzle) = puzzles.get(i) else {
+                        break;
+                    };
+                    // The receiver is only gone if writing failed, so stop early
+                    if sender.send((i, solve_sudoku(puzzle))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Results can arrive out of order, so each one waits until every puzzle before
+        // it has been written
+        let mut pending = BTreeMap::new();
+        let mut written = 0;
+        for (i, result) in receiver {
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&written) {
+                write_record(&mut out, &puzzles[written], result)?;
+                written += 1;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_solve_line() {
+    assert_eq!(
+        solve_line(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+        ),
+        Ok(
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+                .to_string()
+        )
+    );
+
+    assert!(solve_line("53007").is_err());
+    assert!(solve_line(&"1".repeat(81)).is_err());
+}
+
+#[test]
+fn test_solve_to_string() {
+    let solved = solve_to_string(
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+    );
+
+    assert_eq!(
+        solved,
+        Ok("\
+5 3 4 | 6 7 8 | 9 1 2
+6 7 2 | 1 9 5 | 3 4 8
+1 9 8 | 3 4 2 | 5 6 7
+------+-------+------
+8 5 9 | 7 6 1 | 4 2 3
+4 2 6 | 8 5 3 | 7 9 1
+7 1 3 | 9 2 4 | 8 5 6
+------+-------+------
+9 6 1 | 5 3 7 | 2 8 4
+2 8 7 | 4 1 9 | 6 3 5
+3 4 5 | 2 8 6 | 1 7 9
+"
+        .to_string())
+    );
+
+    assert!(solve_to_string("53007").is_err());
+}
+
+#[test]
+fn test_solve_lines() {
+    use crate::puzzle::{PuzzleError, ValidationError};
+
+    let input = "\
+530070000600195000098000060800060003400803001700020006060000280000419005000080079
+
+53007
+550070000600195000098000060800060003400803001700020006060000280000419005000080079
+";
+
+    let results: Vec<Result<String, SolveError>> = solve_lines(input.as_bytes()).collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results[0].as_ref().ok().map(String::as_str),
+        Some("534678912672195348198342567859761423426853791713924856961537284287419635345286179")
+    );
+    assert!(matches!(
+        results[1],
+        Err(SolveError::Parse(PuzzleError::WrongLength(5)))
+    ));
+    assert!(matches!(
+        results[2],
+        Err(SolveError::Invalid(ValidationError::InvalidRow(0)))
+    ));
+
+    // Bytes that aren't UTF-8 can't be read as a line
+    let results: Vec<Result<String, SolveError>> = solve_lines(&b"\xff\n"[..]).collect();
+    assert!(matches!(results[..], [Err(SolveError::Io(_))]));
+}
+
+#[test]
+fn test_solve_batch_jsonl() {
+    let puzzles: Vec<Puzzle> = [
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        "550070000600195000098000060800060003400803001700020006060000280000419005000080079",
+    ]
+    .iter()
+    .map(|line| line.parse().ok().unwrap())
+    .collect();
+
+    let mut out = Vec::new();
+    solve_batch_jsonl(&puzzles, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert_eq!(
+        out.lines().collect::<Vec<_>>(),
+        vec![
+            "{\"input\":\"530070000600195000098000060800060003400803001700020006060000280000419005000080079\",\
+             \"solution\":\"534678912672195348198342567859761423426853791713924856961537284287419635345286179\",\
+             \"error\":null}",
+            "{\"input\":\"550070000600195000098000060800060003400803001700020006060000280000419005000080079\",\
+             \"solution\":null,\
+             \"error\":\"Row 0 of the puzzle is not valid!\"}",
+        ]
+    );
+
+    // Reading the records back gives every field of every puzzle
+    let records: Vec<Vec<(String, Option<String>)>> = out.lines().map(parse_record).collect();
+    assert_eq!(
+        records,
+        vec![
+            vec![
+                ("input".to_string(), Some(puzzles[0].to_line())),
+                (
+                    "solution".to_string(),
+                    Some(solve_sudoku(&puzzles[0]).ok().unwrap().to_line())
+                ),
+                ("error".to_string(), None),
+            ],
+            vec![
+                ("input".to_string(), Some(puzzles[1].to_line())),
+                ("solution".to_string(), None),
+                (
+                    "error".to_string(),
+                    Some("Row 0 of the puzzle is not valid!".to_string())
+                ),
+            ],
+        ]
+    );
+
+    // No puzzles means no output
+    let mut out = Vec::new();
+    solve_batch_jsonl(&[], &mut out).unwrap();
+    assert!(out.is_empty());
+
+    assert_eq!(json_string("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\n\"");
+}
+
+/// Read back a flat JSON object whose values are all strings or `null`, as written by
+/// `solve_batch_jsonl`
+#[cfg(test)]
+fn parse_record(line: &str) -> Vec<(String, Option<String>)> {
+    fn string(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+        assert_eq!(chars.next(), Some('"'));
+        let mut s = String::new();
+        loop {
+            match chars.next().unwrap() {
+                '"' => return s,
+                '\\' => match chars.next().unwrap() {
+                    'n' => s.push('\n'),
+                    'u' => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        s.push(char::from_u32(u32::from_str_radix(&hex, 16).unwrap()).unwrap());
+                    }
+                    c => s.push(c),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    let mut chars = line.chars().peekable();
+    let mut fields = Vec::new();
+    assert_eq!(chars.next(), Some('{'));
+    loop {
+        let key = string(&mut chars);
+        assert_eq!(chars.next(), Some(':'));
+        let value = if chars.peek() == Some(&'"') {
+            Some(string(&mut chars))
+        } else {
+            let null: String = chars.by_ref().take(4).collect();
+            assert_eq!(null, "null");
+            None
+        };
+        fields.push((key, value));
+
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => break,
+            c => panic!("unexpected {:?}", c),
+        }
+    }
+    assert_eq!(chars.next(), None);
+    fields
+}
@@ -0,0 +1,86 @@
+//! Quality checks over a whole pack of puzzles at once.
+
+use std::collections::BTreeMap;
+
+use crate::{difficulty::Difficulty, puzzle::Puzzle, solver::has_unique_solution};
+
+/// A summary of how many puzzles in a pack pass each check
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AuditReport {
+    /// How many puzzles were checked
+    pub total: usize,
+    /// Puzzles whose givens don't break the rules
+    pub valid: usize,
+    /// Puzzles with exactly one solution
+    pub unique: usize,
+    /// Puzzles with exactly one solution that no given can be removed from (see
+    /// `Puzzle::is_minimal`)
+    pub minimal: usize,
+    /// How many of the uniquely solvable puzzles have each difficulty. Difficulties
+    /// that no puzzle has are left out.
+    pub difficulties: BTreeMap<Difficulty, usize>,
+}
+
+/// Check every puzzle in a pack. Only puzzles with a unique solution are checked for
+/// minimality and rated, since neither means much for the others.
+pub fn audit(puzzles: &[Puzzle]) -> AuditReport {
+    let mut report = AuditReport {
+        total: puzzles.len(),
+        ..AuditReport::default()
+    };
+
+    for puzzle in puzzles {
+        if puzzle.validate().is_err() {
+            continue;
+        }
+        report.valid += 1;
+
+        if !has_unique_solution(puzzle) {
+            continue;
+        }
+        report.unique += 1;
+
+        if puzzle.is_minimal() {
+            report.minimal += 1;
+        }
+        *report.difficulties.entry(puzzle.difficulty()).or_insert(0) += 1;
+    }
+
+    report
+}
+
+#[test]
+fn test_audit() {
+    use crate::{generator::minimize, puzzle::Cell};
+
+    let sample = crate::testing::sample();
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    let puzzles = [
+        sample.clone(),
+        minimize(&sample),
+        hard,
+        // Several solutions
+        Puzzle::empty(),
+        // Two 5s in row 0
+        sample.set_cell(2, Cell::Five),
+    ];
+    let report = audit(&puzzles);
+
+    // The hard puzzle is already minimal, and minimizing the sample keeps it easy
+    assert_eq!(
+        report,
+        AuditReport {
+            total: 5,
+            valid: 4,
+            unique: 3,
+            minimal: 2,
+            difficulties: BTreeMap::from([(Difficulty::Easy, 2), (Difficulty::Expert, 1)]),
+        }
+    );
+
+    assert_eq!(audit(&[]), AuditReport::default());
+}
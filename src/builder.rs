@@ -0,0 +1,84 @@
+use crate::{
+    geometry::peers,
+    puzzle::{Cell, Puzzle, PuzzleError},
+};
+
+/// Builds a puzzle one cell at a time without cloning the board for every placement.
+/// Each placement is checked against the earlier ones, and the first conflict is
+/// reported by `build`.
+#[derive(Clone, Debug)]
+pub struct PuzzleBuilder {
+    cells: [Cell; 81],
+    /// The first placement that conflicted with an earlier one, and the cell it
+    /// conflicted with
+    conflict: Option<(usize, usize)>,
+}
+
+impl PuzzleBuilder {
+    pub fn new() -> Self {
+        Self {
+            cells: [Cell::Unset; 81],
+            conflict: None,
+        }
+    }
+
+    /// Put `digit` in the cell at `row` and `col`. A digit that's already in the same
+    /// row, column or block makes `build` fail.
+    pub fn set(&mut self, row: usize, col: usize, digit: Cell) -> &mut Self {
+        debug_assert!(row < 9 && col < 9);
+
+        let index = row * 9 + col;
+        if self.conflict.is_none()
+            && digit.is_set()
+            && let Some(&other) = peers(index).iter().find(|&&peer| self.cells[peer] == digit)
+        {
+            self.conflict = Some((index, other));
+        }
+
+        self.cells[index] = digit;
+        self
+    }
+
+    /// Finish the board, or return the first conflict found while building it
+    pub fn build(self) -> Result<Puzzle, PuzzleError> {
+        match self.conflict {
+            Some((index, other)) => Err(PuzzleError::Conflict(index, other)),
+            None => Ok(Puzzle::new(self.cells)),
+        }
+    }
+}
+
+impl Default for PuzzleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn test_puzzle_builder() {
+    let mut builder = PuzzleBuilder::new();
+    builder
+        .set(0, 0, Cell::Five)
+        .set(0, 1, Cell::Three)
+        .set(0, 4, Cell::Seven)
+        .set(8, 8, Cell::Nine);
+    let puzzle = builder.build().ok().unwrap();
+
+    let expected = Puzzle::empty()
+        .set_cell(0, Cell::Five)
+        .set_cell(1, Cell::Three)
+        .set_cell(4, Cell::Seven)
+        .set_cell(80, Cell::Nine);
+    assert_eq!(puzzle, expected);
+
+    // The second 5 shares a column with the first
+    let mut builder = PuzzleBuilder::new();
+    builder
+        .set(0, 0, Cell::Five)
+        .set(1, 1, Cell::Two)
+        .set(6, 0, Cell::Five)
+        .set(7, 7, Cell::One);
+    assert!(matches!(builder.build(), Err(PuzzleError::Conflict(54, 0))));
+
+    assert_eq!(PuzzleBuilder::default().build().ok(), Some(Puzzle::empty()));
+}
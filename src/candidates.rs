@@ -0,0 +1,155 @@
+use crate::puzzle::{Cell, Puzzle};
+
+/// Candidate mask with all of the digits 1-9 set
+pub const ALL_DIGITS: u16 = 0b1_1111_1111;
+
+/// The candidate digits of every cell on a sudoku board. Each cell's candidates are
+/// stored as a bitmask (see `Cell::mask`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Candidates {
+    masks: [u16; 81],
+}
+
+impl Candidates {
+    pub fn new(masks: [u16; 81]) -> Self {
+        Self { masks }
+    }
+
+    /// Compute the candidates of every cell in a puzzle. Set cells have their own value
+    /// as their only candidate.
+    pub fn from_puzzle(puzzle: &Puzzle) -> Self {
+        let mut masks = [0; 81];
+
+        for (i, mask) in masks.iter_mut().enumerate() {
+            let cell = puzzle.get_cell(i);
+            *mask = if cell.is_set() {
+                cell.mask()
+            } else {
+                puzzle.possibilities_mask(i)
+            };
+        }
+
+        Self::new(masks)
+    }
+
+    /// A board with every cell that has a single candidate set to that digit, and every
+    /// other cell unset
+    pub fn to_puzzle(&self) -> Puzzle {
+        Puzzle::new(std::array::from_fn(|i| match self.masks[i].count_ones() {
+            1 => Cell::from(self.masks[i].trailing_zeros() as u8 + 1),
+            _ => Cell::Unset,
+        }))
+    }
+
+    pub fn mask(&self, index: usize) -> u16 {
+        self.masks[index]
+    }
+
+    pub fn set_mask(&mut self, index: usize, mask: u16) {
+        debug_assert!(mask & !ALL_DIGITS == 0);
+
+        self.masks[index] = mask;
+    }
+
+    /// Whether `digit` is still a candidate for the cell at `index`
+    pub fn contains(&self, index: usize, digit: Cell) -> bool {
+        self.masks[index] & digit.mask() != 0
+    }
+
+    /// Add `digit` to the candidates of the cell at `index`
+    pub fn insert(&mut self, index: usize, digit: Cell) {
+        self.masks[index] |= digit.mask();
+    }
+
+    /// Remove `digit` from the candidates of the cell at `index`. Returns whether the
+    /// digit was a candidate before the removal.
+    pub fn remove(&mut self, index: usize, digit: Cell) -> bool {
+        let present = self.contains(index, digit);
+        self.masks[index] &= !digit.mask();
+        present
+    }
+}
+
+impl Puzzle {
+    /// The candidates of every cell as bitmasks (see `Cell::mask`). Set cells have their
+    /// own value as their only candidate. This is a common format for passing candidate
+    /// states to other solvers.
+    pub fn candidates_bits(&self) -> [u16; 81] {
+        let candidates = Candidates::from_puzzle(self);
+        std::array::from_fn(|i| candidates.mask(i))
+    }
+
+    /// Read a board back from candidate bitmasks (see `candidates_bits`). Cells with a
+    /// single candidate are set to it, and every other cell is unset.
+    pub fn from_candidate_bits(bits: &[u16; 81]) -> Puzzle {
+        Candidates::new(*bits).to_puzzle()
+    }
+}
+
+/// The candidates that both `a` and `b` allow, for combining the deductions of two
+/// independent passes over the same board
+pub fn intersect_candidates(a: &Candidates, b: &Candidates) -> Candidates {
+    Candidates::new(std::array::from_fn(|i| a.masks[i] & b.masks[i]))
+}
+
+/// The candidates that either `a` or `b` allows
+pub fn union_candidates(a: &Candidates, b: &Candidates) -> Candidates {
+    Candidates::new(std::array::from_fn(|i| a.masks[i] | b.masks[i]))
+}
+
+/// Iterate over the digits in a candidate mask, in ascending order
+pub fn digits(mask: u16) -> impl Iterator<Item = Cell> {
+    (1..=9u8)
+        .map(Cell::from)
+        .filter(move |digit| mask & digit.mask() != 0)
+}
+
+#[test]
+fn test_candidate_bits() {
+    use crate::techniques::eliminate_hidden_singles;
+
+    let puzzle = crate::testing::sample();
+    let bits = puzzle.candidates_bits();
+    assert_eq!(bits[0], Cell::Five.mask());
+    // Clues survive the round trip
+    let imported = Puzzle::from_candidate_bits(&bits);
+    for (i, cell) in puzzle.iter_set_cells() {
+        assert_eq!(imported.get_cell(i), cell);
+    }
+
+    // Propagating turns some cells into singletons, which come back as set cells
+    let mut candidates = Candidates::new(bits);
+    eliminate_hidden_singles(&mut candidates);
+    let propagated: [u16; 81] = std::array::from_fn(|i| candidates.mask(i));
+
+    let imported = Puzzle::from_candidate_bits(&propagated);
+    for (i, &mask) in propagated.iter().enumerate() {
+        assert_eq!(imported.get_cell(i).is_set(), mask.count_ones() == 1);
+        if mask.count_ones() == 1 {
+            assert_eq!(imported.get_cell(i).mask(), mask);
+        }
+    }
+    assert!(imported.clue_count() > puzzle.clue_count());
+}
+
+#[test]
+fn test_combine_candidates() {
+    let puzzle = crate::testing::sample();
+    let candidates = Candidates::from_puzzle(&puzzle);
+    let everything = Candidates::new([ALL_DIGITS; 81]);
+    let nothing = Candidates::new([0; 81]);
+
+    assert_eq!(intersect_candidates(&candidates, &candidates), candidates);
+    assert_eq!(intersect_candidates(&candidates, &everything), candidates);
+    assert_eq!(intersect_candidates(&candidates, &nothing), nothing);
+    assert_eq!(union_candidates(&candidates, &candidates), candidates);
+    assert_eq!(union_candidates(&candidates, &nothing), candidates);
+    assert_eq!(union_candidates(&candidates, &everything), everything);
+
+    // Two passes that each ruled out a different digit in cell 2
+    let (mut a, mut b) = (candidates, candidates);
+    a.remove(2, Cell::One);
+    b.remove(2, Cell::Two);
+    assert_eq!(intersect_candidates(&a, &b).mask(2), Cell::Four.mask());
+    assert_eq!(union_candidates(&a, &b), candidates);
+}
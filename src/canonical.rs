@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+
+use crate::puzzle::{Cell, Puzzle};
+
+/// Every ordering of three things
+const PERMUTATIONS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+impl Puzzle {
+    /// A deterministic representative of all the puzzles that are equivalent to this one.
+    /// Two puzzles canonicalize to the same board exactly when one can be turned into the
+    /// other by permuting bands, stacks, rows within a band, and columns within a stack,
+    /// transposing, and relabeling digits.
+    ///
+    /// The representative is the smallest board (comparing cells in order, with unset
+    /// cells as 0) that can be reached with those transformations.
+    pub fn canonicalize(&self) -> Puzzle {
+        let grid: [u8; 81] = std::array::from_fn(|i| u8::from(self.get_cell(i)));
+        let transposed: [u8; 81] = std::array::from_fn(|i| grid[(i % 9) * 9 + i / 9]);
+
+        let mut search = Search {
+            best: None,
+            generation: 0,
+            current: [0; 81],
+        };
+
+        for grid in [&grid, &transposed] {
+            for stacks in PERMUTATIONS {
+                for a in PERMUTATIONS {
+                    for b in PERMUTATIONS {
+                        for c in PERMUTATIONS {
+                            let within = [a, b, c];
+                            let columns: [usize; 9] =
+                                std::array::from_fn(|i| stacks[i / 3] * 3 + within[i / 3][i % 3]);
+
+                            let start = RowState {
+                                position: 0,
+                                used_rows: 0,
+                                band: 0,
+                                labels: [0; 10],
+                                next_label: 1,
+                                less: false,
+                                generation: search.generation,
+                            };
+                            search.rows(grid, &columns, start);
+                        }
+                    }
+                }
+            }
+        }
+
+        Puzzle::new(search.best.unwrap().map(Cell::from))
+    }
+}
+
+/// Search for the smallest board reachable by reordering rows, for a fixed column order
+struct Search {
+    best: Option<[u8; 81]>,
+    /// Incremented every time `best` is replaced
+    generation: u64,
+    /// The board being built
+    current: [u8; 81],
+}
+
+/// The state of the search after some output rows have been filled in
+#[derive(Clone, Copy)]
+struct RowState {
+    /// The output row being filled in
+    position: usize,
+    /// Bitmask of the input rows that have already been used
+    used_rows: u16,
+    /// The input band that the current output band is taken from
+    band: usize,
+    /// The output label of each input digit, or 0 if it hasn't appeared yet
+    labels: [u8; 10],
+    next_label: u8,
+    /// Whether the rows so far are already smaller than `best`. Only meaningful while
+    /// `best` is still from `generation`.
+    less: bool,
+    generation: u64,
+}
+
+impl Search {
+    /// Try every input row that can go at `state.position`, relabeling digits in order
+    /// of their first appearance, and keep exploring the ones that could still beat the
+    /// best board.
+    fn rows(&mut self, grid: &[u8; 81], columns: &[usize; 9], state: RowState) {
+        let position = state.position;
+        if position == 9 {
+            if self.best.is_none() || (state.less && state.generation == self.generation) {
+                self.best = Some(self.current);
+                self.generation += 1;
+            }
+            return;
+        }
+
+        // The first row of an output band can come from any unused input band. The
+        // other two rows have to come from the same band.
+        let bands = if position.is_multiple_of(3) {
+            0..3
+        } else {
+            state.band..state.band + 1
+        };
+
+        for band in bands {
+            for row in band * 3..band * 3 + 3 {
+                if state.used_rows & (1 << row) != 0 {
+                    continue;
+                }
+
+                let mut next = state;
+                next.position += 1;
+                next.used_rows |= 1 << row;
+                next.band = band;
+
+                let out = &mut self.current[position * 9..position * 9 + 9];
+                for (k, column) in columns.iter().enumerate() {
+                    let value = grid[row * 9 + column] as usize;
+                    out[k] = if value == 0 {
+                        0
+                    } else {
+                        if next.labels[value] == 0 {
+                            next.labels[value] = next.next_label;
+                            next.next_label += 1;
+                        }
+                        next.labels[value]
+                    };
+                }
+
+                // Everything before this row is equal to the best board unless `less`
+                // is still valid, in which case the new row doesn't matter
+                if let Some(best) = &self.best
+                    && !(state.less && state.generation == self.generation)
+                {
+                    match (*out).cmp(&best[position * 9..position * 9 + 9]) {
+                        Ordering::Greater => continue,
+                        Ordering::Less => next.less = true,
+                        Ordering::Equal => next.less = false,
+                    }
+                }
+                next.generation = self.generation;
+
+                self.rows(grid, columns, next);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_canonicalize() {
+    let puzzle = crate::testing::sample();
+
+    let canonical = puzzle.canonicalize();
+    assert_eq!(canonical.canonicalize(), canonical);
+    assert_eq!(canonical.clue_count(), puzzle.clue_count());
+
+    use Cell::*;
+    let relabeled = puzzle
+        .relabel([Nine, Eight, Seven, Six, Five, Four, Three, Two, One])
+        .ok()
+        .unwrap();
+    // Move the top band to the bottom
+    let mut shifted = Puzzle::empty();
+    for i in 0..81 {
+        shifted = shifted.set_cell((i + 27) % 81, puzzle.get_cell(i));
+    }
+
+    for equivalent in [
+        puzzle.rotate90(),
+        puzzle.transpose(),
+        puzzle.reflect_horizontal(),
+        relabeled,
+        shifted,
+    ] {
+        assert_eq!(equivalent.canonicalize(), canonical);
+    }
+
+    // Removing a clue makes a different puzzle
+    assert_ne!(puzzle.unset_cell(0).canonicalize(), canonical);
+}
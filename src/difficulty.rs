@@ -0,0 +1,126 @@
+use crate::{
+    candidates::Candidates,
+    puzzle::Puzzle,
+    solver::solve_with_stats,
+    techniques::{Technique, apply_technique},
+};
+
+/// How hard a puzzle is for a human, based on the hardest technique needed to solve it
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Difficulty {
+    /// Solvable with naked and hidden singles alone
+    Easy,
+    /// Needs locked candidates or triples
+    Medium,
+    /// Needs X-Wings, Swordfish or XY-Wings
+    Hard,
+    /// Can't be solved without guessing
+    Expert,
+}
+
+impl Technique {
+    /// The difficulty of a puzzle that needs this technique
+    pub fn difficulty(self) -> Difficulty {
+        match self {
+            Technique::NakedSingle | Technique::HiddenSingle => Difficulty::Easy,
+            Technique::LockedCandidates | Technique::NakedTriple | Technique::HiddenTriple => {
+                Difficulty::Medium
+            }
+            Technique::XWing | Technique::Swordfish | Technique::XyWing => Difficulty::Hard,
+        }
+    }
+}
+
+impl Puzzle {
+    /// Rate how hard the puzzle is. Techniques are applied easiest first, going back to
+    /// the easiest one after every change, so a harder technique only counts when
+    /// nothing simpler makes progress. Puzzles that these techniques can't finish are
+    /// `Expert`.
+    pub fn difficulty(&self) -> Difficulty {
+        match self.hardest_technique() {
+            (hardest, _, true) => {
+                hardest.map_or(Difficulty::Easy, |i| Technique::ALL[i].difficulty())
+            }
+            (_, _, false) => Difficulty::Expert,
+        }
+    }
+
+    /// A finer grained rating than `difficulty`, where higher is harder. Puzzles that the
+    /// techniques can finish score 10 points per step up `Technique::ALL` for the hardest
+    /// technique they need, up to 80. The rest score 100, plus one for every wrong
+    /// guess the solver makes on the board the techniques leave behind.
+    pub fn difficulty_score(&self) -> u32 {
+        match self.hardest_technique() {
+            (hardest, _, true) => hardest.map_or(0, |i| (i as u32 + 1) * 10),
+            (_, candidates, false) => {
+                let (_, stats) = solve_with_stats(&candidates.to_puzzle());
+                100 + stats.backtracks as u32
+            }
+        }
+    }
+
+    /// Apply the techniques the way `difficulty` describes. Returns the index in
+    /// `Technique::ALL` of the hardest one that made progress (`None` if none did), the
+    /// candidates they leave behind, and whether those solve the puzzle.
+    fn hardest_technique(&self) -> (Option<usize>, Candidates, bool) {
+        let mut candidates = Candidates::from_puzzle(self);
+        let mut hardest = None;
+
+        while let Some(i) = Technique::ALL
+            .into_iter()
+            .position(|technique| apply_technique(&mut candidates, technique))
+        {
+            hardest = hardest.max(Some(i));
+        }
+
+        let solved = (0..81).all(|i| candidates.mask(i).count_ones() == 1);
+        (hardest, candidates, solved)
+    }
+}
+
+#[test]
+fn test_difficulty() {
+    // The techniques are listed easiest first
+    assert!(
+        Technique::ALL
+            .windows(2)
+            .all(|pair| pair[0].difficulty() <= pair[1].difficulty())
+    );
+
+    let easy = crate::testing::sample();
+    assert_eq!(easy.difficulty(), Difficulty::Easy);
+
+    let expert: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    assert_eq!(expert.difficulty(), Difficulty::Expert);
+
+    assert!(Difficulty::Easy < Difficulty::Medium);
+    assert!(Difficulty::Hard < Difficulty::Expert);
+}
+
+#[test]
+fn test_difficulty_score() {
+    let easy = crate::testing::sample();
+    let x_wing: Puzzle =
+        "100000569492056108056109240009640801064010000218035604040500016905061402621000005"
+            .parse()
+            .ok()
+            .unwrap();
+    let expert: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+
+    // Naked singles are the first tier and X-Wings the sixth
+    assert_eq!(easy.difficulty_score(), 10);
+    assert_eq!(x_wing.difficulty(), Difficulty::Hard);
+    assert_eq!(x_wing.difficulty_score(), 60);
+    assert!(expert.difficulty_score() > 100);
+
+    let solved = crate::solver::solve_sudoku(&easy).ok().unwrap();
+    assert_eq!(solved.difficulty_score(), 0);
+}
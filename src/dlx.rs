@@ -0,0 +1,243 @@
+//! Sudoku as an exact cover problem, solved with Knuth's Algorithm X using dancing links.
+//!
+//! Every (cell, digit) placement is a row of the matrix, and it covers four constraint
+//! columns: the cell is filled, and the digit appears in the cell's row, column, and block.
+//! A solution is a set of rows that covers every column exactly once.
+
+use crate::puzzle::{Cell, Puzzle};
+
+/// 81 cells, plus 81 (unit, digit) pairs for each of rows, columns, and blocks
+const COLUMNS: usize = 324;
+
+/// Index of the root node, which links the column headers together
+const ROOT: usize = 0;
+
+struct Matrix {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// The column header each node belongs to
+    column: Vec<usize>,
+    /// The placement (`cell * 9 + digit - 1`) each node belongs to
+    placement: Vec<usize>,
+    /// The number of nodes left in each column, indexed by header node
+    size: Vec<usize>,
+    /// The first node of each placement's row
+    first_node: Vec<usize>,
+}
+
+impl Matrix {
+    fn new() -> Self {
+        let mut matrix = Self {
+            left: Vec::new(),
+            right: Vec::new(),
+            up: Vec::new(),
+            down: Vec::new(),
+            column: Vec::new(),
+            placement: Vec::new(),
+            size: vec![0; COLUMNS + 1],
+            first_node: Vec::with_capacity(729),
+        };
+
+        // The root and the column headers, linked in a circular list
+        for i in 0..=COLUMNS {
+            matrix.left.push(if i == 0 { COLUMNS } else { i - 1 });
+            matrix.right.push(if i == COLUMNS { 0 } else { i + 1 });
+            matrix.up.push(i);
+            matrix.down.push(i);
+            matrix.column.push(i);
+            matrix.placement.push(usize::MAX);
+        }
+
+        for cell in 0..81 {
+            let (row, column) = (cell / 9, cell % 9);
+            let block = (row / 3) * 3 + column / 3;
+
+            for digit in 0..9 {
+                // Header node indexes are offset by one because of the root
+                let headers = [
+                    1 + cell,
+                    1 + 81 + row * 9 + digit,
+                    1 + 162 + column * 9 + digit,
+                    1 + 243 + block * 9 + digit,
+                ];
+                matrix.add_row(cell * 9 + digit, headers);
+            }
+        }
+
+        matrix
+    }
+
+    fn add_row(&mut self, placement: usize, headers: [usize; 4]) {
+        let first = self.left.len();
+        self.first_node.push(first);
+
+        for (i, header) in headers.into_iter().enumerate() {
+            let node = first + i;
+
+            self.left.push(if i == 0 { first + 3 } else { node - 1 });
+            self.right.push(if i == 3 { first } else { node + 1 });
+
+            // Insert at the bottom of the column
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let last = self.up[header];
+            self.down[last] = node;
+            self.up[header] = node;
+
+            self.column.push(header);
+            self.placement.push(placement);
+            self.size[header] += 1;
+        }
+    }
+
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    /// Run Algorithm X, pushing the placements of the solution onto `solution`
+    fn search(&mut self, solution: &mut Vec<usize>) -> bool {
+        if self.right[ROOT] == ROOT {
+            return true;
+        }
+
+        // Branch on the column with the fewest remaining rows
+        let mut header = self.right[ROOT];
+        let mut j = self.right[header];
+        while j != ROOT {
+            if self.size[j] < self.size[header] {
+                header = j;
+            }
+            j = self.right[j];
+        }
+
+        if self.size[header] == 0 {
+            return false;
+        }
+
+        self.cover(header);
+
+        let mut r = self.down[header];
+        while r != header {
+            solution.push(self.placement[r]);
+
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+
+            if self.search(solution) {
+                return true;
+            }
+
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+
+            solution.pop();
+            r = self.down[r];
+        }
+
+        self.uncover(header);
+        false
+    }
+}
+
+/// Solve a puzzle by reducing it to exact cover. Returns `None` if the puzzle has no
+/// solution, including when its givens conflict with each other.
+pub fn solve_dlx(puzzle: &Puzzle) -> Option<Puzzle> {
+    let mut matrix = Matrix::new();
+    let mut covered = [false; COLUMNS + 1];
+
+    // Select the row of every given up front
+    for (cell_index, cell) in puzzle.iter_set_cells() {
+        let first = matrix.first_node[cell_index * 9 + u8::from(cell) as usize - 1];
+
+        for node in first..first + 4 {
+            let header = matrix.column[node];
+            if covered[header] {
+                // Two givens satisfy the same constraint
+                return None;
+            }
+            covered[header] = true;
+            matrix.cover(header);
+        }
+    }
+
+    let mut solution = Vec::with_capacity(81);
+    if !matrix.search(&mut solution) {
+        return None;
+    }
+
+    let mut solved = puzzle.clone();
+    for placement in solution {
+        solved = solved.set_cell(placement / 9, Cell::from((placement % 9) as u8 + 1));
+    }
+    Some(solved)
+}
+
+#[test]
+fn test_solve_dlx() {
+    use crate::solver::solve_sudoku;
+
+    let puzzle = crate::testing::sample();
+
+    let solved = solve_dlx(&puzzle).unwrap();
+    assert!(solved.is_solved());
+    assert_eq!(Some(solved), solve_sudoku(&puzzle).ok());
+
+    // A 17 clue puzzle
+    #[rustfmt::skip]
+    let puzzle = Puzzle::from_bytes(&[
+        0, 0, 0, 0, 0, 0, 0, 1, 0,
+        4, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 2, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 5, 0, 4, 0, 7,
+        0, 0, 8, 0, 0, 0, 3, 0, 0,
+        0, 0, 1, 0, 9, 0, 0, 0, 0,
+        3, 0, 0, 4, 0, 0, 2, 0, 0,
+        0, 5, 0, 1, 0, 0, 0, 0, 0,
+        0, 0, 0, 8, 0, 6, 0, 0, 0
+    ]).ok().unwrap();
+    assert!(solve_dlx(&puzzle).is_some_and(|p| p.is_solved()));
+
+    // Conflicting givens
+    let puzzle = Puzzle::empty()
+        .set_cell(0, Cell::Four)
+        .set_cell(8, Cell::Four);
+    assert_eq!(solve_dlx(&puzzle), None);
+}
@@ -0,0 +1,408 @@
+//! Step-by-step solving that explains each deduction the way a person would, for
+//! teaching how a puzzle is solved.
+
+use crate::{
+    candidates::Candidates,
+    geometry::{block_indexes, column_indexes, coords, peers, row_indexes},
+    puzzle::{Cell, Contradiction, Puzzle, UnitKind},
+    solver::{SolveError, solve_sudoku},
+    techniques::{Technique, apply_technique},
+};
+
+/// A cell in the `R4C5` notation, counting from 1
+fn notation(index: usize) -> String {
+    let (row, column, _) = coords(index);
+    format!("R{}C{}", row + 1, column + 1)
+}
+
+/// Fill in a digit and remove it from the candidates of the cell's peers
+fn place(board: &mut Puzzle, candidates: &mut Candidates, index: usize, digit: Cell) {
+    *board = board.set_cell(index, digit);
+    candidates.set_mask(index, digit.mask());
+    for &peer in peers(index) {
+        candidates.remove(peer, digit);
+    }
+}
+
+/// Every unset cell that only has one candidate left, in index order
+fn naked_singles<'a>(
+    board: &'a Puzzle,
+    candidates: &'a Candidates,
+) -> impl Iterator<Item = (usize, Cell, String)> + 'a {
+    board
+        .iter_unset_cells()
+        .filter(|&(i, _)| candidates.mask(i).count_ones() == 1)
+        .map(|(i, _)| {
+            let digit = Cell::from(candidates.mask(i).trailing_zeros() as u8 + 1);
+            (i, digit, "only candidate left".to_string())
+        })
+}
+
+/// Every digit that can only go in one unset cell of a row, column or block. A cell can
+/// come up more than once if it is the only spot in several units.
+fn hidden_singles<'a>(
+    board: &'a Puzzle,
+    candidates: &'a Candidates,
+) -> impl Iterator<Item = (usize, Cell, String)> + 'a {
+    let units = (0..9).flat_map(|i| {
+        [
+            ("row", i, row_indexes(i)),
+            ("column", i, column_indexes(i)),
+            ("block", i, block_indexes(i)),
+        ]
+    });
+
+    units.flat_map(move |(kind, number, unit)| {
+        (1..=9).map(Cell::from).filter_map(move |digit| {
+            let mut cells = unit.into_iter().filter(|&i| candidates.contains(i, digit));
+
+            match (cells.next(), cells.next()) {
+                (Some(index), None) if !board.get_cell(index).is_set() => {
+                    let reason = format!("only spot in {} {}", kind, number + 1);
+                    Some((index, digit, reason))
+                }
+                _ => None,
+            }
+        })
+    })
+}
+
+/// The first naked single, or if there are none the first hidden single, along with
+/// the technique that found it
+fn next_single(
+    board: &Puzzle,
+    candidates: &Candidates,
+) -> Option<(Technique, (usize, Cell, String))> {
+    naked_singles(board, candidates)
+        .next()
+        .map(|found| (Technique::NakedSingle, found))
+        .or_else(|| {
+            hidden_singles(board, candidates)
+                .next()
+                .map(|found| (Technique::HiddenSingle, found))
+        })
+}
+
+impl Puzzle {
+    /// The placement a person would most likely find next. Naked singles come first,
+    /// then hidden singles, and if there are neither, the other techniques are used to
+    /// remove candidates until one turns up. Returns `None` if the board breaks the
+    /// rules or no placement can be found without guessing.
+    pub fn easiest_next_cell(&self) -> Option<(usize, Cell)> {
+        if self.validate().is_err() || self.has_contradiction() {
+            return None;
+        }
+
+        let mut candidates = Candidates::from_puzzle(self);
+        loop {
+            if let Some((_, (index, digit, _))) = next_single(self, &candidates) {
+                return Some((index, digit));
+            }
+
+            if !Technique::ALL
+                .iter()
+                .any(|&technique| apply_technique(&mut candidates, technique))
+            {
+                return None;
+            }
+        }
+    }
+
+    /// Every placement that follows straight from the board as it is now: unset cells
+    /// with only one candidate, and digits with only one spot left in a row, column or
+    /// block. Nothing is placed, so one placement making another possible doesn't count.
+    /// The placements are listed by cell index.
+    pub fn forced_cells(&self) -> Vec<(usize, Cell)> {
+        let candidates = Candidates::from_puzzle(self);
+
+        let mut forced: Vec<(usize, Cell)> = naked_singles(self, &candidates)
+            .chain(hidden_singles(self, &candidates))
+            .map(|(index, digit, _)| (index, digit))
+            .collect();
+
+        forced.sort_unstable();
+        forced.dedup();
+        forced
+    }
+
+    /// Why the puzzle has no solution, or `None` if it has one. Givens that break the
+    /// rules are reported first, then unset cells without any candidates, then digits
+    /// that can't go anywhere in a row, column or block. Rows, columns and blocks are
+    /// counted from 1.
+    pub fn explain_unsolvable(&self) -> Option<String> {
+        if let Some(&(a, b)) = self.all_conflicts().first() {
+            return Some(format!(
+                "cells {} and {} both have a {}",
+                notation(a),
+                notation(b),
+                u8::from(self.get_cell(a))
+            ));
+        }
+
+        match self.find_contradiction() {
+            Some(Contradiction::NoCandidates(i)) => {
+                Some(format!("cell {} has no candidates", notation(i)))
+            }
+            Some(Contradiction::Unplaceable(digit, kind, number)) => {
+                let kind = match kind {
+                    UnitKind::Row => "row",
+                    UnitKind::Column => "column",
+                    UnitKind::Block => "block",
+                };
+                Some(format!(
+                    "digit {} cannot be placed anywhere in {} {}",
+                    u8::from(digit),
+                    kind,
+                    number + 1
+                ))
+            }
+            None if self.is_solvable() => None,
+            None => Some("every way of filling in the board leads to a conflict".to_string()),
+        }
+    }
+}
+
+/// Solve a puzzle the way a person would, trying the easiest deduction first at every
+/// step, and describe each step in a line of the log. Cells are filled in with naked
+/// and hidden singles, and the other techniques only remove candidates. If no
+/// technique makes progress, the rest of the board is filled in by guessing, which is
+/// logged too.
+pub fn solve_with_log(puzzle: &Puzzle) -> (Puzzle, Vec<String>) {
+    if let Err(e) = puzzle.validate() {
+        return (puzzle.clone(), vec![e.to_string()]);
+    }
+
+    let mut board = puzzle.clone();
+    let mut candidates = Candidates::from_puzzle(puzzle);
+    let mut log = Vec::new();
+
+    while !board.is_complete() {
+        if (0..81).any(|i| candidates.mask(i) == 0) {
+            log.push("A cell has no candidates left, so the puzzle has no solution".to_string());
+            return (board, log);
+        }
+
+        if let Some((technique, (index, digit, reason))) = next_single(&board, &candidates) {
+            log.push(format!(
+                "{}: {} goes in {} ({})",
+                technique,
+                u8::from(digit),
+                notation(index),
+                reason
+            ));
+            place(&mut board, &mut candidates, index, digit);
+            continue;
+        }
+
+        let before = candidates;
+        if let Some(&technique) = Technique::ALL
+            .iter()
+            .find(|&&technique| apply_technique(&mut candidates, technique))
+        {
+            let removed: u32 = (0..81)
+                .map(|i| (before.mask(i) & !candidates.mask(i)).count_ones())
+                .sum();
+            log.push(format!("{}: removed {} candidates", technique, removed));
+            continue;
+        }
+
+        log.push("No technique makes progress, so the rest has to be guessed".to_string());
+        match solve_sudoku(&board) {
+            Ok(solved) => board = solved,
+            Err(e) => log.push(e.to_string()),
+        }
+        break;
+    }
+
+    (board, log)
+}
+
+/// The puzzle with just the first `k` cells that `solve_with_log` would fill in, in the
+/// same order, for revealing a solution one deduction at a time. If the puzzle has
+/// fewer than `k` unset cells it's returned solved. Fails if a cell would have to be
+/// guessed before `k` cells are filled in.
+pub fn solve_reveal(puzzle: &Puzzle, k: usize) -> Result<Puzzle, SolveError> {
+    puzzle.validate()?;
+
+    let mut board = puzzle.clone();
+    let mut candidates = Candidates::from_puzzle(puzzle);
+    let mut placed = 0;
+
+    while placed < k && !board.is_complete() {
+        if (0..81).any(|i| candidates.mask(i) == 0) {
+            return Err(SolveError::NoSolution);
+        }
+
+        if let Some((_, (index, digit, _))) = next_single(&board, &candidates) {
+            place(&mut board, &mut candidates, index, digit);
+            placed += 1;
+        } else if !Technique::ALL
+            .iter()
+            .any(|&technique| apply_technique(&mut candidates, technique))
+        {
+            return Err(SolveError::RequiresGuessing);
+        }
+    }
+
+    Ok(board)
+}
+
+#[test]
+fn test_solve_with_log() {
+    // Naked singles are enough for this puzzle, so every step fills in a cell
+    let puzzle = crate::testing::sample();
+    let (solved, log) = solve_with_log(&puzzle);
+
+    assert_eq!(solved, solve_sudoku(&puzzle).ok().unwrap());
+    assert_eq!(log.len(), 81 - puzzle.clue_count());
+    assert!(log.iter().all(|line| line.starts_with("Naked single: ")));
+    assert!(log.contains(&"Naked single: 4 goes in R1C3 (only candidate left)".to_string()));
+
+    // This one needs guessing
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    let (solved, log) = solve_with_log(&hard);
+    assert_eq!(solved, solve_sudoku(&hard).ok().unwrap());
+    assert!(log.last().unwrap().contains("guessed"));
+}
+
+#[test]
+fn test_hidden_single_reason() {
+    use crate::candidates::ALL_DIGITS;
+
+    // 7 can only go in R3C6 within block 2, but the cell has other candidates too
+    let mut candidates = Candidates::new([ALL_DIGITS; 81]);
+    for i in block_indexes(1).into_iter().filter(|&i| i != 23) {
+        candidates.remove(i, Cell::Seven);
+    }
+
+    assert_eq!(
+        hidden_singles(&Puzzle::empty(), &candidates).collect::<Vec<_>>(),
+        vec![(23, Cell::Seven, "only spot in block 2".to_string())]
+    );
+}
+
+#[test]
+fn test_easiest_next_cell() {
+    let puzzle = crate::testing::sample();
+    let solution = solve_sudoku(&puzzle).ok().unwrap();
+
+    // This board has hidden singles too, but naked singles win
+    let candidates = Candidates::from_puzzle(&puzzle);
+    assert!(naked_singles(&puzzle, &candidates).next().is_some());
+    assert!(hidden_singles(&puzzle, &candidates).next().is_some());
+
+    let (index, digit) = puzzle.easiest_next_cell().unwrap();
+    assert_eq!(puzzle.possibilities(index).len(), 1);
+    assert_eq!(digit, solution.get_cell(index));
+
+    // Nothing short of guessing makes progress on this one
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    assert_eq!(hard.easiest_next_cell(), None);
+
+    assert_eq!(solution.easiest_next_cell(), None);
+    assert_eq!(puzzle.set_cell(2, Cell::Five).easiest_next_cell(), None);
+}
+
+#[test]
+fn test_explain_unsolvable() {
+    let puzzle = crate::testing::sample();
+    assert_eq!(puzzle.explain_unsolvable(), None);
+    assert_eq!(
+        puzzle.set_cell(2, Cell::Five).explain_unsolvable(),
+        Some("cells R1C1 and R1C3 both have a 5".to_string())
+    );
+
+    // The center cell sees every digit, but none of them twice
+    let mut cells = [Cell::Unset; 81];
+    for (i, digit) in [36, 37, 38, 39, 41, 42, 43, 44].into_iter().zip(1..=8) {
+        cells[i] = Cell::from(digit);
+    }
+    cells[4] = Cell::Nine;
+    assert_eq!(
+        Puzzle::new(cells).explain_unsolvable(),
+        Some("cell R5C5 has no candidates".to_string())
+    );
+
+    // 1 can't go anywhere in the first row
+    let mut cells = [Cell::Unset; 81];
+    cells[0] = Cell::Two;
+    cells[1] = Cell::Three;
+    for i in [12, 24, 38] {
+        cells[i] = Cell::One;
+    }
+    assert_eq!(
+        Puzzle::new(cells).explain_unsolvable(),
+        Some("digit 1 cannot be placed anywhere in row 1".to_string())
+    );
+}
+
+#[test]
+fn test_solve_reveal() {
+    let puzzle = crate::testing::sample();
+    let solution = solve_sudoku(&puzzle).ok().unwrap();
+
+    assert_eq!(solve_reveal(&puzzle, 0).ok(), Some(puzzle.clone()));
+
+    let mut previous = puzzle.clone();
+    for k in 1..=5 {
+        let revealed = solve_reveal(&puzzle, k).ok().unwrap();
+        assert_eq!(revealed.clue_count(), puzzle.clue_count() + k);
+        assert_eq!(revealed.cells_filled_since(&previous), 1);
+        for (i, cell) in revealed.iter_set_cells() {
+            assert_eq!(cell, solution.get_cell(i));
+        }
+        previous = revealed;
+    }
+
+    // The first cell revealed is the easiest one
+    let (index, digit) = puzzle.easiest_next_cell().unwrap();
+    assert_eq!(
+        solve_reveal(&puzzle, 1).ok(),
+        Some(puzzle.set_cell(index, digit))
+    );
+    assert_eq!(solve_reveal(&puzzle, 81).ok(), Some(solution));
+
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    assert!(matches!(
+        solve_reveal(&hard, 1),
+        Err(SolveError::RequiresGuessing)
+    ));
+    assert!(matches!(
+        solve_reveal(&puzzle.set_cell(2, Cell::Five), 1),
+        Err(SolveError::Invalid(_))
+    ));
+}
+
+#[test]
+fn test_forced_cells() {
+    // Row 0 leaves only 1 for cell 0. The 1s in cells 16, 33, 57 and 67 rule out every
+    // other spot for a 1 in block 8, but cell 80 itself still has other candidates.
+    let mut puzzle = Puzzle::empty();
+    for (i, digit) in (2..=9).map(Cell::from).enumerate() {
+        puzzle = puzzle.set_cell(i + 1, digit);
+    }
+    for i in [16, 33, 57, 67] {
+        puzzle = puzzle.set_cell(i, Cell::One);
+    }
+
+    let forced = puzzle.forced_cells();
+    assert!(forced.contains(&(0, Cell::One)));
+    assert!(forced.contains(&(80, Cell::One)));
+    assert!(puzzle.possibilities(80).len() > 1);
+    assert!(forced.iter().all(|&(i, _)| !puzzle.get_cell(i).is_set()));
+    assert!(forced.windows(2).all(|pair| pair[0] < pair[1]));
+
+    assert!(Puzzle::empty().forced_cells().is_empty());
+}
@@ -0,0 +1,297 @@
+use crate::{
+    difficulty::Difficulty,
+    geometry::block_indexes,
+    puzzle::{Cell, Puzzle},
+    rng::SplitMix64,
+    solver::{has_unique_solution, solve_random, solve_sudoku},
+};
+
+/// Generate a random complete grid. The three blocks on the diagonal don't share any
+/// rows or columns, so they are filled with shuffled digits straight away, and the rest
+/// of the board is solved with each cell's candidates tried in a random order.
+pub fn random_solution(rng: &mut SplitMix64) -> Puzzle {
+    let mut board = Puzzle::empty();
+    for block in [0, 4, 8] {
+        let mut digits: Vec<Cell> = (1..=9).map(Cell::from).collect();
+        rng.shuffle(&mut digits);
+        let placements: Vec<(usize, Cell)> = block_indexes(block).into_iter().zip(digits).collect();
+        board = board.set_cells(&placements);
+    }
+
+    match solve_random(&board, rng.next_u64()) {
+        Some(solved) => solved,
+        None => unreachable!("any filling of the diagonal blocks can be completed"),
+    }
+}
+
+/// Generate a puzzle with a unique solution whose clues are symmetric under a 180
+/// degree rotation: cell `i` is a given exactly when cell `80 - i` is.
+pub fn generate_symmetric(seed: u64) -> Puzzle {
+    let mut rng = SplitMix64::new(seed);
+    let mut puzzle = random_solution(&mut rng);
+
+    // Cell 40 is the center, which is its own partner
+    let mut pairs: Vec<usize> = (0..=40).collect();
+    rng.shuffle(&mut pairs);
+
+    for i in pairs {
+        let candidate = puzzle.unset_cell(i).unset_cell(80 - i);
+        if has_unique_solution(&candidate) {
+            puzzle = candidate;
+        }
+    }
+
+    puzzle
+}
+
+/// How many puzzles `generate_with_difficulty` tries before settling for the closest one
+const DIFFICULTY_ATTEMPTS: usize = 20;
+
+/// Generate a symmetric puzzle with a unique solution whose `difficulty` is `target`.
+/// Puzzles that come out too hard get clues from their solution added back until they
+/// are easy enough. Returns the puzzle along with its actual difficulty: if none of the
+/// attempts hits the target exactly, that is the closest puzzle found, so callers that
+/// need the exact difficulty should check it.
+pub fn generate_with_difficulty(seed: u64, target: Difficulty) -> (Puzzle, Difficulty) {
+    let mut rng = SplitMix64::new(seed);
+    let mut closest: Option<(Puzzle, Difficulty)> = None;
+
+    for _ in 0..DIFFICULTY_ATTEMPTS {
+        let mut puzzle = generate_symmetric(rng.next_u64());
+        let mut difficulty = puzzle.difficulty();
+
+        if difficulty > target {
+            let solution = match solve_sudoku(&puzzle) {
+                Ok(solved) => solved,
+                Err(_) => unreachable!("generated puzzles always have a solution"),
+            };
+
+            // Adding clues keeps the solution unique and can only make the puzzle easier
+            let mut blanks: Vec<usize> =
+                (0..=40).filter(|&i| !puzzle.get_cell(i).is_set()).collect();
+            rng.shuffle(&mut blanks);
+
+            for i in blanks {
+                puzzle = puzzle
+                    .set_cell(i, solution.get_cell(i))
+                    .set_cell(80 - i, solution.get_cell(80 - i));
+                difficulty = puzzle.difficulty();
+                if difficulty <= target {
+                    break;
+                }
+            }
+        }
+
+        if difficulty == target {
+            return (puzzle, difficulty);
+        }
+
+        let distance = |d: Difficulty| (d as i32 - target as i32).abs();
+        if closest
+            .as_ref()
+            .is_none_or(|&(_, best)| distance(difficulty) < distance(best))
+        {
+            closest = Some((puzzle, difficulty));
+        }
+    }
+
+    closest.expect("at least one puzzle is generated")
+}
+
+/// How many grids `generate_with_clues` tries before giving up
+const CLUE_ATTEMPTS: usize = 20;
+
+/// Generate a puzzle with a unique solution and exactly `clue_count` givens. Givens are
+/// removed from a random grid in a random order, skipping any whose removal would allow
+/// another solution. Returns `None` for fewer than 17 clues, since no sudoku with fewer
+/// has a unique solution, or if none of the attempts got down to `clue_count`.
+pub fn generate_with_clues(seed: u64, clue_count: usize) -> Option<Puzzle> {
+    if !(17..=81).contains(&clue_count) {
+        return None;
+    }
+
+    let mut rng = SplitMix64::new(seed);
+    for _ in 0..CLUE_ATTEMPTS {
+        let mut puzzle = random_solution(&mut rng);
+        let mut cells: Vec<usize> = (0..81).collect();
+        rng.shuffle(&mut cells);
+
+        for i in cells {
+            if puzzle.clue_count() == clue_count {
+                break;
+            }
+
+            let candidate = puzzle.unset_cell(i);
+            if has_unique_solution(&candidate) {
+                puzzle = candidate;
+            }
+        }
+
+        if puzzle.clue_count() == clue_count {
+            return Some(puzzle);
+        }
+    }
+
+    None
+}
+
+/// Remove as many givens as possible while keeping a unique solution. Removing any
+/// given from the result would allow more than one solution. Puzzles that don't have a
+/// unique solution to begin with are returned unchanged.
+pub fn minimize(puzzle: &Puzzle) -> Puzzle {
+    let mut puzzle = puzzle.clone();
+    if !has_unique_solution(&puzzle) {
+        return puzzle;
+    }
+
+    // A single pass is enough: if removing a given allows several solutions, removing
+    // it after taking away even more givens would too
+    let givens: Vec<usize> = puzzle.iter_set_cells().map(|(i, _)| i).collect();
+    for i in givens {
+        let candidate = puzzle.unset_cell(i);
+        if has_unique_solution(&candidate) {
+            puzzle = candidate;
+        }
+    }
+
+    puzzle
+}
+
+impl Puzzle {
+    /// Whether the puzzle has a unique solution that removing any one of its givens
+    /// would break, meaning `minimize` couldn't remove anything
+    pub fn is_minimal(&self) -> bool {
+        has_unique_solution(self) && self.redundant_givens().is_empty()
+    }
+
+    /// The indexes of the givens that could each be removed on their own with the puzzle
+    /// still having a unique solution. Removing one can make others necessary, so they
+    /// can't always all be removed together (see `minimize` for that).
+    pub fn redundant_givens(&self) -> Vec<usize> {
+        self.iter_set_cells()
+            .map(|(i, _)| i)
+            .filter(|&i| has_unique_solution(&self.unset_cell(i)))
+            .collect()
+    }
+}
+
+#[test]
+fn test_random_solution() {
+    let mut rng = SplitMix64::new(7);
+    let first = random_solution(&mut rng);
+    let second = random_solution(&mut rng);
+
+    assert!(first.is_complete());
+    assert!(first.is_solved());
+    assert!(second.is_solved());
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_generate_symmetric() {
+    for seed in 0..3 {
+        let puzzle = generate_symmetric(seed);
+
+        for i in 0..81 {
+            assert_eq!(
+                puzzle.get_cell(i).is_set(),
+                puzzle.get_cell(80 - i).is_set()
+            );
+        }
+        assert!(has_unique_solution(&puzzle));
+        assert!(puzzle.clue_count() < 81);
+    }
+
+    assert_eq!(generate_symmetric(42), generate_symmetric(42));
+}
+
+#[test]
+fn test_generate_with_difficulty() {
+    use crate::{
+        candidates::Candidates,
+        techniques::{eliminate_hidden_singles, eliminate_naked_singles},
+    };
+
+    for seed in 0..3 {
+        let (puzzle, difficulty) = generate_with_difficulty(seed, Difficulty::Easy);
+        assert_eq!(difficulty, Difficulty::Easy);
+        assert_eq!(puzzle.difficulty(), Difficulty::Easy);
+        assert!(has_unique_solution(&puzzle));
+
+        // Singles alone are enough to fill in every cell
+        let mut candidates = Candidates::from_puzzle(&puzzle);
+        while eliminate_naked_singles(&mut candidates) || eliminate_hidden_singles(&mut candidates)
+        {
+        }
+        assert!((0..81).all(|i| candidates.mask(i).count_ones() == 1));
+    }
+
+    let (puzzle, difficulty) = generate_with_difficulty(5, Difficulty::Medium);
+    assert_eq!(puzzle.difficulty(), difficulty);
+    assert_eq!(
+        generate_with_difficulty(5, Difficulty::Medium),
+        (puzzle, difficulty)
+    );
+}
+
+#[test]
+fn test_minimize() {
+    let puzzle = crate::testing::sample();
+
+    let minimal = minimize(&puzzle);
+    assert!(minimal.clue_count() < puzzle.clue_count());
+    assert!(has_unique_solution(&minimal));
+
+    for (i, cell) in minimal.iter_set_cells() {
+        assert_eq!(puzzle.get_cell(i), cell);
+        assert!(!has_unique_solution(&minimal.unset_cell(i)));
+    }
+}
+
+#[test]
+fn test_is_minimal() {
+    // There are no uniquely solvable puzzles with 16 clues, so every 17 clue one is
+    // minimal
+    let seventeen: Puzzle =
+        "000000010400000000020000000000050407008000300001090000300400200050100000000806000"
+            .parse()
+            .ok()
+            .unwrap();
+    assert!(seventeen.is_minimal());
+
+    let puzzle = crate::testing::sample();
+    assert!(!puzzle.is_minimal());
+    assert!(minimize(&puzzle).is_minimal());
+
+    assert!(!Puzzle::empty().is_minimal());
+}
+
+#[test]
+fn test_generate_with_clues() {
+    for clue_count in [30, 45, 81] {
+        let puzzle = generate_with_clues(1, clue_count).unwrap();
+        assert_eq!(puzzle.clue_count(), clue_count);
+        assert!(has_unique_solution(&puzzle));
+    }
+
+    assert_eq!(generate_with_clues(1, 16), None);
+    assert_eq!(generate_with_clues(1, 82), None);
+    assert_eq!(generate_with_clues(4, 30), generate_with_clues(4, 30));
+}
+
+#[test]
+fn test_redundant_givens() {
+    let puzzle = crate::testing::sample();
+    let solution = solve_sudoku(&puzzle).ok().unwrap();
+
+    let minimal = minimize(&puzzle);
+    assert!(minimal.redundant_givens().is_empty());
+
+    // Any extra given on a minimal puzzle can be taken away again
+    let extra = (0..81).find(|&i| !minimal.get_cell(i).is_set()).unwrap();
+    let padded = minimal.set_cell(extra, solution.get_cell(extra));
+    assert!(padded.redundant_givens().contains(&extra));
+
+    // Without a unique solution nothing can be removed
+    assert!(Puzzle::empty().redundant_givens().is_empty());
+}
@@ -0,0 +1,142 @@
+use std::sync::OnceLock;
+
+/// Lookup tables describing which cells belong together, computed once on first use
+struct Tables {
+    /// For each cell, the indexes of the cells in its row, column, and block (in that order)
+    units: [[[usize; 9]; 3]; 81],
+    /// For each cell, the indexes of the 20 other cells that share a unit with it, sorted
+    peers: [[usize; 20]; 81],
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let mut units = [[[0; 9]; 3]; 81];
+        let mut peers = [[0; 20]; 81];
+
+        for index in 0..81 {
+            let (row, column, block) = coords(index);
+
+            units[index] = [
+                row_indexes(row),
+                column_indexes(column),
+                block_indexes(block),
+            ];
+
+            let mut cell_peers: Vec<usize> = units[index]
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|&i| i != index)
+                .collect();
+            cell_peers.sort_unstable();
+            cell_peers.dedup();
+            peers[index].copy_from_slice(&cell_peers);
+        }
+
+        Tables { units, peers }
+    })
+}
+
+/// The indexes of the cells in the row, column, and block (in that order) containing
+/// the cell at `index`
+pub fn units(index: usize) -> &'static [[usize; 9]; 3] {
+    &tables().units[index]
+}
+
+/// The indexes of the 20 other cells that share a row, column, or block with the cell
+/// at `index`
+pub fn peers(index: usize) -> &'static [usize; 20] {
+    &tables().peers[index]
+}
+
+/// The row, column, and block indexes of the cell at `index`
+pub fn coords(index: usize) -> (usize, usize, usize) {
+    debug_assert!(index < 81);
+
+    (index / 9, index % 9, block_of_cell(index))
+}
+
+/// The block (0-8, row by row) that the cell at `cell_index` is in. Everything that
+/// needs to know which block a cell is in goes through this.
+pub fn block_of_cell(cell_index: usize) -> usize {
+    debug_assert!(cell_index < 81);
+
+    (cell_index / 27) * 3 + (cell_index % 9) / 3
+}
+
+/// The indexes of the cells in a row, from left to right
+pub fn row_indexes(row: usize) -> [usize; 9] {
+    std::array::from_fn(|i| row * 9 + i)
+}
+
+/// The indexes of the cells in a column, from top to bottom
+pub fn column_indexes(column: usize) -> [usize; 9] {
+    std::array::from_fn(|i| column + i * 9)
+}
+
+/// The indexes of the cells in a block, row by row
+pub fn block_indexes(block: usize) -> [usize; 9] {
+    let start = (block / 3) * 27 + (block % 3) * 3;
+    std::array::from_fn(|i| start + (i / 3) * 9 + i % 3)
+}
+
+/// One of the 27 rows, columns and blocks, by its index (0-8)
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Block(usize),
+}
+
+impl Unit {
+    /// The indexes of the unit's cells, in the same order as `row_indexes`,
+    /// `column_indexes` and `block_indexes`
+    pub fn indexes(&self) -> [usize; 9] {
+        match *self {
+            Unit::Row(row) => row_indexes(row),
+            Unit::Column(column) => column_indexes(column),
+            Unit::Block(block) => block_indexes(block),
+        }
+    }
+}
+
+/// Whether two cells are in the same row, column, or block
+pub(crate) fn shares_unit(a: usize, b: usize) -> bool {
+    let same_row = a / 9 == b / 9;
+    let same_column = a % 9 == b % 9;
+    let same_block = block_of_cell(a) == block_of_cell(b);
+
+    same_row || same_column || same_block
+}
+
+#[test]
+fn test_cached_peers() {
+    for index in 0..81 {
+        let expected: Vec<usize> = (0..81)
+            .filter(|&i| i != index && shares_unit(index, i))
+            .collect();
+        assert_eq!(peers(index).to_vec(), expected);
+
+        for unit in units(index) {
+            assert!(unit.contains(&index));
+            assert!(
+                unit.iter()
+                    .all(|&i| i == index || peers(index).contains(&i))
+            );
+        }
+    }
+
+    assert_eq!(units(40)[2], [30, 31, 32, 39, 40, 41, 48, 49, 50]);
+}
+
+#[test]
+fn test_coords() {
+    assert_eq!(coords(0), (0, 0, 0));
+    assert_eq!(coords(40), (4, 4, 4));
+    assert_eq!(coords(80), (8, 8, 8));
+    assert_eq!(coords(8), (0, 8, 2));
+    assert_eq!(coords(33), (3, 6, 5));
+    assert_eq!(coords(72), (8, 0, 6));
+}
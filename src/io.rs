@@ -0,0 +1,312 @@
+use std::str::FromStr;
+
+use crate::puzzle::{Cell, Puzzle, PuzzleError};
+
+/// Parse a single character of a puzzle, where `.` or `0` is an unset cell
+fn parse_cell(c: char) -> Result<Cell, PuzzleError> {
+    match c {
+        '.' | '0' => Ok(Cell::Unset),
+        '1'..='9' => Ok(Cell::from(c as u8 - b'0')),
+        _ => Err(PuzzleError::InvalidCharacter(c)),
+    }
+}
+
+/// Parse a puzzle in the SadMan `.sdk` format: nine lines of nine characters, with `.`
+/// for blank cells. Lines starting with `#` hold metadata and are ignored.
+pub fn from_sdk(text: &str) -> Result<Puzzle, PuzzleError> {
+    let mut values = Vec::with_capacity(81);
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        for c in line.chars() {
+            values.push(u8::from(parse_cell(c)?));
+        }
+    }
+
+    Puzzle::from_bytes(&values)
+}
+
+/// Parse a puzzle drawn as a grid, like one copied from a newspaper. Each row is a line
+/// with `.` or `0` for blanks. Whitespace and the `|`, `-`, `+` and `=` characters used
+/// to draw box borders are ignored, so separator lines like `------+-------+------` are
+/// skipped entirely.
+pub fn read_grid(text: &str) -> Result<Puzzle, PuzzleError> {
+    let values = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '|' | '-' | '+' | '='))
+        .map(|c| parse_cell(c).map(u8::from))
+        .collect::<Result<Vec<u8>, PuzzleError>>()?;
+
+    Puzzle::from_bytes(&values)
+}
+
+/// Parse a puzzle written as a single line of 81 characters
+fn parse_line(line: &str) -> Result<Puzzle, PuzzleError> {
+    let values = line
+        .chars()
+        .map(|c| parse_cell(c).map(u8::from))
+        .collect::<Result<Vec<u8>, PuzzleError>>()?;
+
+    Puzzle::from_bytes(&values)
+}
+
+/// Parse a puzzle stored as CSV: nine rows of nine comma separated fields, where empty
+/// fields and `0` are unset cells. Spaces around fields are ignored, and so are blank
+/// lines.
+pub fn from_csv(text: &str) -> Result<Puzzle, PuzzleError> {
+    let mut values = Vec::with_capacity(81);
+
+    for (row, line) in text.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 9 {
+            return Err(PuzzleError::OnLine(
+                row,
+                Box::new(PuzzleError::WrongFieldCount(fields.len())),
+            ));
+        }
+
+        for (column, field) in fields.into_iter().enumerate() {
+            let value = match field.as_bytes() {
+                [] => 0,
+                &[c @ b'0'..=b'9'] => c - b'0',
+                _ => return Err(PuzzleError::InvalidField(row, column)),
+            };
+            values.push(value);
+        }
+    }
+
+    Puzzle::from_bytes(&values)
+}
+
+/// Parse the puzzles in an `.sdm` document, which has one puzzle of 81 characters per
+/// line. Blank lines are skipped.
+pub fn load_sdm(text: &str) -> Result<Vec<Puzzle>, PuzzleError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_line(line.trim()).map_err(|e| PuzzleError::OnLine(i, Box::new(e))))
+        .collect()
+}
+
+/// Parse a puzzle written as a single line of 81 characters, with `.` or `0` for
+/// unset cells
+impl FromStr for Puzzle {
+    type Err = PuzzleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_line(s.trim())
+    }
+}
+
+impl Puzzle {
+    /// Write the puzzle as a single line of 81 digits, with `0` for unset cells
+    pub fn to_line(&self) -> String {
+        (0..81)
+            .map(|i| (b'0' + u8::from(self.get_cell(i))) as char)
+            .collect()
+    }
+
+    /// Write the puzzle in the `.sdk` format (see `from_sdk`)
+    pub fn to_sdk(&self) -> String {
+        let mut out = String::with_capacity(90);
+
+        for i in 0..81 {
+            out.push(match u8::from(self.get_cell(i)) {
+                0 => '.',
+                value => (b'0' + value) as char,
+            });
+
+            if i % 9 == 8 {
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+}
+
+/// The index of a cell written in the `R4C5` notation used by solving guides, where
+/// rows and columns are counted from 1. Either letter can be lowercase.
+pub fn index_of_notation(notation: &str) -> Result<usize, PuzzleError> {
+    let invalid = || PuzzleError::InvalidNotation(notation.to_string());
+
+    let rest = notation.strip_prefix(['R', 'r']).ok_or_else(invalid)?;
+    let (row, column) = rest.split_once(['C', 'c']).ok_or_else(invalid)?;
+
+    let parse = |s: &str| match s.parse::<usize>() {
+        Ok(n @ 1..=9) if s.len() == 1 => Ok(n - 1),
+        _ => Err(invalid()),
+    };
+    Ok(parse(row)? * 9 + parse(column)?)
+}
+
+impl Puzzle {
+    /// The cell at a position written like `R4C5` (see `index_of_notation`)
+    pub fn cell_at(&self, notation: &str) -> Result<Cell, PuzzleError> {
+        Ok(self.get_cell(index_of_notation(notation)?))
+    }
+}
+
+#[test]
+fn test_sdk() {
+    let document = "\
+#AArto Inkala
+#DA notoriously difficult puzzle
+8........
+..36.....
+.7..9.2..
+.5...7...
+....457..
+...1...3.
+..1....68
+..85...1.
+.9....4..
+";
+
+    let puzzle = from_sdk(document).ok().unwrap();
+    assert_eq!(puzzle.get_cell(0), Cell::Eight);
+    assert_eq!(puzzle.get_cell(11), Cell::Three);
+    assert_eq!(puzzle.clue_count(), 21);
+
+    // Metadata isn't kept, so the round trip only matches the grid lines
+    let grid_only: String = document
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    assert_eq!(puzzle.to_sdk(), grid_only);
+    assert_eq!(from_sdk(&puzzle.to_sdk()).ok(), Some(puzzle));
+
+    assert!(matches!(
+        from_sdk("8....x..."),
+        Err(PuzzleError::InvalidCharacter('x'))
+    ));
+    assert!(matches!(
+        from_sdk("8........\n"),
+        Err(PuzzleError::WrongLength(9))
+    ));
+}
+
+#[test]
+fn test_read_grid() {
+    let grid = "\
++-------+-------+-------+
+| 5 3 . | . 7 . | . . . |
+| 6 . . | 1 9 5 | . . . |
+| . 9 8 | . . . | . 6 . |
++-------+-------+-------+
+| 8 . . | . 6 . | . . 3 |
+| 4 . . | 8 . 3 | . . 1 |
+| 7 . . | . 2 . | . . 6 |
++-------+-------+-------+
+| . 6 . | . . . | 2 8 . |
+| . . . | 4 1 9 | . . 5 |
+| . . . | . 8 . | . 7 9 |
++-------+-------+-------+
+";
+
+    let puzzle = read_grid(grid).ok().unwrap();
+    assert_eq!(puzzle, crate::testing::sample());
+
+    // Extra spaces and zeros for blanks work too
+    let plain = "  530 070 000\n600 195 000\n098 000 060\n800 060 003\n400 803 001\n\
+        700 020 006\n060 000 280\n000 419 005\n000 080 079   \n";
+    assert_eq!(read_grid(plain).ok(), Some(puzzle));
+
+    assert!(matches!(
+        read_grid("| 5 3 x |"),
+        Err(PuzzleError::InvalidCharacter('x'))
+    ));
+}
+
+#[test]
+fn test_load_sdm() {
+    let document = "\
+530070000600195000098000060800060003400803001700020006060000280000419005000080079
+
+800000000003600000070090200050007000000045700000100030001000068008500010090000400
+";
+
+    let puzzles = load_sdm(document).ok().unwrap();
+    assert_eq!(puzzles.len(), 2);
+    assert_eq!(puzzles[0].get_cell(0), Cell::Five);
+    assert_eq!(puzzles[1].get_cell(0), Cell::Eight);
+    assert_eq!(puzzles[1].to_sdk().lines().nth(1), Some("..36....."));
+
+    let broken = format!("{}123", document);
+    match load_sdm(&broken) {
+        Err(PuzzleError::OnLine(3, e)) => assert!(matches!(*e, PuzzleError::WrongLength(3))),
+        _ => panic!("expected an error on line 3"),
+    }
+}
+
+#[test]
+fn test_line() {
+    let line = "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    let puzzle: Puzzle = line.parse().ok().unwrap();
+    assert_eq!(puzzle.to_line(), line);
+    assert_eq!(line.replace('0', ".").parse::<Puzzle>().ok(), Some(puzzle));
+}
+
+#[test]
+fn test_notation() {
+    assert!(matches!(index_of_notation("R1C1"), Ok(0)));
+    assert!(matches!(index_of_notation("R9C9"), Ok(80)));
+    assert!(matches!(index_of_notation("r4c5"), Ok(31)));
+
+    for invalid in ["", "R0C1", "R1C10", "R10C1", "R1", "C1R1", "R+1C1", "R1C1 "] {
+        assert!(matches!(
+            index_of_notation(invalid),
+            Err(PuzzleError::InvalidNotation(_))
+        ));
+    }
+
+    let puzzle = crate::testing::sample();
+    assert!(matches!(puzzle.cell_at("R1C2"), Ok(Cell::Three)));
+    assert!(matches!(puzzle.cell_at("R9C9"), Ok(Cell::Nine)));
+    assert!(matches!(puzzle.cell_at("R1C3"), Ok(Cell::Unset)));
+    assert!(puzzle.cell_at("R1C0").is_err());
+}
+
+#[test]
+fn test_from_csv() {
+    let csv = "\
+5,3,,,7,,,,
+6,,,1,9,5,,,
+,9,8,,,,,6,
+8,,,,6,,,,3
+4,,,8,,3,,,1
+7,,,,2,,,,6
+,6,,,,,2,8,
+,,,4,1,9,,,5
+0,0,0,0,8,0,0,7,9
+";
+    let puzzle = from_csv(csv).ok().unwrap();
+    assert_eq!(puzzle, crate::testing::sample());
+
+    let short_row = csv.replacen("6,,,1,9,5,,,", "6,,,1,9,5,,", 1);
+    assert!(matches!(
+        from_csv(&short_row),
+        Err(PuzzleError::OnLine(1, e)) if matches!(*e, PuzzleError::WrongFieldCount(8))
+    ));
+
+    let bad_field = csv.replacen(",9,8,", ",9,x,", 1);
+    assert!(matches!(
+        from_csv(&bad_field),
+        Err(PuzzleError::InvalidField(2, 2))
+    ));
+    assert!(matches!(
+        from_csv(&csv.replacen("5,3", "5,10", 1)),
+        Err(PuzzleError::InvalidField(0, 1))
+    ));
+
+    // Only eight rows
+    assert!(matches!(
+        from_csv(csv.split_once('\n').unwrap().1),
+        Err(PuzzleError::WrongLength(72))
+    ));
+}
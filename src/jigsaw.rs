@@ -0,0 +1,216 @@
+//! Jigsaw sudoku, where the nine 3x3 blocks are replaced by nine irregular regions of
+//! nine cells each. Rows and columns work the same as in a normal sudoku.
+
+use std::collections::HashSet;
+
+use crate::{
+    candidates::{ALL_DIGITS, digits},
+    geometry::coords,
+    puzzle::{Cell, Puzzle, PuzzleError, ValidationError},
+    solver::SolveError,
+};
+
+/// The region (0-8) that each cell of a jigsaw sudoku belongs to
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Regions([usize; 81]);
+
+impl Regions {
+    /// Every region id has to be in the range 0-8 and be used by exactly nine cells
+    pub fn new(regions: [usize; 81]) -> Result<Self, PuzzleError> {
+        let mut sizes = [0; 9];
+
+        for &region in regions.iter() {
+            if region >= 9 {
+                return Err(PuzzleError::InvalidRegion(region));
+            }
+            sizes[region] += 1;
+        }
+
+        match sizes.iter().position(|&size| size != 9) {
+            Some(region) => Err(PuzzleError::InvalidRegion(region)),
+            None => Ok(Self(regions)),
+        }
+    }
+
+    /// The regions of a normal sudoku, which are its 3x3 blocks
+    pub fn blocks() -> Self {
+        Self(std::array::from_fn(|i| coords(i).2))
+    }
+
+    /// The region that the cell at `index` belongs to
+    pub fn region_of(&self, index: usize) -> usize {
+        self.0[index]
+    }
+
+    /// The indexes of the cells in a region, in ascending order
+    pub fn cells(&self, region: usize) -> Vec<usize> {
+        (0..81).filter(|&i| self.0[i] == region).collect()
+    }
+}
+
+impl Puzzle {
+    /// Like `possibilities`, but with `regions` taking the place of the blocks
+    pub fn region_possibilities(&self, cell_index: usize, regions: &Regions) -> HashSet<Cell> {
+        let (row, column, _) = coords(cell_index);
+        let region = regions.region_of(cell_index);
+
+        let mut set: HashSet<Cell> = (1..=9).map(Cell::from).collect();
+        for i in (0..81).filter(|&i| i != cell_index) {
+            let (r, c, _) = coords(i);
+            if r == row || c == column || regions.region_of(i) == region {
+                set.remove(&self.get_cell(i));
+            }
+        }
+
+        set
+    }
+}
+
+/// Backtracking search for jigsaw puzzles. The digits used by every row, column and
+/// region are kept as bitmasks, so a cell's candidates are whatever none of its three
+/// units use yet.
+struct JigsawSearch<'a> {
+    cells: [Cell; 81],
+    regions: &'a Regions,
+    rows: [u16; 9],
+    columns: [u16; 9],
+    region_masks: [u16; 9],
+}
+
+impl JigsawSearch<'_> {
+    fn candidates(&self, index: usize) -> u16 {
+        let (row, column, _) = coords(index);
+        ALL_DIGITS
+            & !(self.rows[row] | self.columns[column] | self.region_masks[self.regions.0[index]])
+    }
+
+    fn toggle(&mut self, index: usize, digit: Cell) {
+        let (row, column, _) = coords(index);
+        self.rows[row] ^= digit.mask();
+        self.columns[column] ^= digit.mask();
+        self.region_masks[self.regions.0[index]] ^= digit.mask();
+    }
+
+    fn solve(&mut self) -> bool {
+        // Branch on the unset cell with the fewest candidates
+        let Some(index) = (0..81)
+            .filter(|&i| !self.cells[i].is_set())
+            .min_by_key(|&i| self.candidates(i).count_ones())
+        else {
+            return true;
+        };
+
+        for digit in digits(self.candidates(index)) {
+            self.cells[index] = digit;
+            self.toggle(index, digit);
+
+            if self.solve() {
+                return true;
+            }
+
+            self.toggle(index, digit);
+            self.cells[index] = Cell::Unset;
+        }
+
+        false
+    }
+}
+
+/// Solve a jigsaw sudoku, where each of the nine `regions` has to contain the digits 1-9
+/// instead of each block
+pub fn solve_jigsaw(puzzle: &Puzzle, regions: &Regions) -> Result<Puzzle, SolveError> {
+    let mut search = JigsawSearch {
+        cells: [Cell::Unset; 81],
+        regions,
+        rows: [0; 9],
+        columns: [0; 9],
+        region_masks: [0; 9],
+    };
+
+    for (i, cell) in puzzle.iter_set_cells() {
+        let (row, column, _) = coords(i);
+        if search.rows[row] & cell.mask() != 0 {
+            return Err(ValidationError::InvalidRow(row).into());
+        }
+        if search.columns[column] & cell.mask() != 0 {
+            return Err(ValidationError::InvalidColumn(column).into());
+        }
+        if search.region_masks[regions.region_of(i)] & cell.mask() != 0 {
+            return Err(ValidationError::InvalidBlock(regions.region_of(i)).into());
+        }
+
+        search.cells[i] = cell;
+        search.toggle(i, cell);
+    }
+
+    if search.solve() {
+        Ok(Puzzle::new(search.cells))
+    } else {
+        Err(SolveError::NoSolution)
+    }
+}
+
+#[test]
+fn test_regions() {
+    assert!(Regions::new([0; 81]).is_err());
+    assert!(matches!(
+        Regions::new(std::array::from_fn(|i| i % 10)),
+        Err(PuzzleError::InvalidRegion(9))
+    ));
+
+    let blocks = Regions::blocks();
+    assert_eq!(Regions::new(blocks.0).ok(), Some(blocks.clone()));
+    assert_eq!(blocks.cells(4), vec![30, 31, 32, 39, 40, 41, 48, 49, 50]);
+
+    let puzzle = crate::testing::sample();
+    for (i, _) in puzzle.iter_unset_cells() {
+        assert_eq!(
+            puzzle.region_possibilities(i, &blocks),
+            puzzle.possibilities(i)
+        );
+    }
+}
+
+#[test]
+fn test_solve_jigsaw() {
+    // 000111222
+    // 000112222
+    // 030111122
+    // 330444455
+    // 333444555
+    // 333445555
+    // 666677888
+    // 666777888
+    // 667777888
+    let layout =
+        "000111222000112222030111122330444455333444555333445555666677888666777888667777888";
+    let regions = Regions::new(std::array::from_fn(|i| {
+        (layout.as_bytes()[i] - b'0') as usize
+    }))
+    .ok()
+    .unwrap();
+
+    let puzzle: Puzzle =
+        "030070010072000000008342500809001420000800000013004006061000080087000035340000100"
+            .parse()
+            .ok()
+            .unwrap();
+
+    let solved = solve_jigsaw(&puzzle, &regions).ok().unwrap();
+    assert_eq!(solved, crate::testing::sample_solution());
+
+    for region in 0..9 {
+        let present: HashSet<Cell> = regions
+            .cells(region)
+            .into_iter()
+            .map(|i| solved.get_cell(i))
+            .collect();
+        assert_eq!(present.len(), 9);
+    }
+
+    // Cell 0 shares a region with the 9 in cell 29, but not a row, column or block
+    assert!(matches!(
+        solve_jigsaw(&puzzle.set_cell(0, Cell::Nine), &regions),
+        Err(SolveError::Invalid(ValidationError::InvalidBlock(0)))
+    ));
+}
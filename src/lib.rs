@@ -0,0 +1,25 @@
+pub mod api;
+pub mod audit;
+pub mod builder;
+pub mod candidates;
+pub mod canonical;
+pub mod difficulty;
+pub mod dlx;
+pub mod explain;
+pub mod generator;
+pub mod geometry;
+pub mod io;
+pub mod jigsaw;
+pub mod puzzle;
+pub mod rectangular;
+pub mod render;
+pub mod rng;
+pub mod solver;
+pub mod sort;
+pub mod state;
+pub mod symmetry;
+pub mod techniques;
+#[cfg(test)]
+mod testing;
+pub mod variant;
+pub mod verify;
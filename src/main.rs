@@ -1,17 +1,18 @@
-use crate::{
-    puzzle::{Cell, Puzzle},
-    solver::solve_sudoku,
-};
+use std::io::{IsTerminal, Read, stdin, stdout};
 
-mod puzzle;
-mod solver;
-mod sort;
+use zognorp::{io::read_grid, puzzle::Puzzle, render::render_diff, solver::solve_sudoku};
 
 fn main() {
-    let mut grid: [Cell; 81] = [Cell::Unset; 81];
+    // A puzzle piped in on stdin takes the place of the built in one
+    let mut input = String::new();
+    let piped = !stdin().is_terminal();
+    if piped && let Err(e) = stdin().read_to_string(&mut input) {
+        println!("{}", e);
+        return;
+    }
 
     #[rustfmt::skip]
-    [
+    let puzzle = Puzzle::from_bytes(&[
         5, 3, 0, 0, 7, 0, 0, 0, 0,
         6, 0, 0, 1, 9, 5, 0, 0, 0,
         0, 9, 8, 0, 0, 0, 0, 6, 0,
@@ -20,19 +21,26 @@ fn main() {
         7, 0, 0, 0, 2, 0, 0, 0, 6,
         0, 6, 0, 0, 0, 0, 2, 8, 0,
         0, 0, 0, 4, 1, 9, 0, 0, 5,
-        0, 0, 0, 0, 8, 0, 0, 7, 9 
-    ]
-        .iter()
-        .map(|c| Cell::from(*c))
-        .enumerate()
-        .for_each(|(i, c)| grid[i] = c);
+        0, 0, 0, 0, 8, 0, 0, 7, 9
+    ]);
+    let puzzle = if piped { read_grid(&input) } else { puzzle };
 
-    println!("67: {:?}", grid[67]);
+    let puzzle = match puzzle {
+        Ok(p) => p,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
 
-    let puzzle = Puzzle::new(grid);
+    println!("67: {:?}", puzzle.get_cell(67));
 
-    match solve_sudoku(puzzle) {
-        Ok(p) => println!("Found a solution!"),
+    match solve_sudoku(&puzzle) {
+        Ok(solved) => {
+            println!("Found a solution!");
+            // Only color the solver's cells when someone is looking
+            print!("{}", render_diff(&puzzle, &solved, stdout().is_terminal()));
+        }
         Err(e) => println!("{}", e),
     }
 }
@@ -1,8 +1,15 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, error::Error, fmt::Display};
+
+use crate::{
+    candidates::{ALL_DIGITS, digits},
+    geometry::{Unit, block_indexes, column_indexes, peers, row_indexes, shares_unit},
+    rng::SplitMix64,
+};
 
 // Represents all the possible values that can be held in a Sudoku cell
-#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
 pub enum Cell {
+    #[default]
     Unset,
     One,
     Two,
@@ -19,11 +26,14 @@ impl Cell {
     pub fn is_set(&self) -> bool {
         *self != Cell::Unset
     }
-}
 
-impl Default for Cell {
-    fn default() -> Self {
-        Self::Unset
+    /// The cell's value as a candidate bitmask, where bit `n - 1` represents the digit `n`.
+    /// `Unset` has an empty mask.
+    pub fn mask(&self) -> u16 {
+        match u8::from(*self) {
+            0 => 0,
+            value => 1 << (value - 1),
+        }
     }
 }
 
@@ -68,8 +78,126 @@ impl From<Cell> for u8 {
     }
 }
 
+#[derive(Debug)]
+pub enum PuzzleError {
+    /// A board needs exactly 81 cells, but this many were provided
+    WrongLength(usize),
+    /// The value at this index isn't in the range 0-9
+    InvalidValue(usize, u8),
+    /// A digit mapping doesn't use each of the digits 1-9 exactly once
+    NotAPermutation,
+    /// Text contained a character that doesn't represent a cell
+    InvalidCharacter(char),
+    /// An error on a particular line (counting from 0) of a multi-line document
+    OnLine(usize, Box<PuzzleError>),
+    /// A jigsaw region id is out of range, or the region doesn't have exactly nine cells
+    InvalidRegion(usize),
+    /// The cell at the first index has the same digit as the one at the second index,
+    /// which is in the same row, column or block
+    Conflict(usize, usize),
+    /// A cell reference isn't in the `R4C5` form, or its row or column isn't in 1-9
+    InvalidNotation(String),
+    /// Regions of this width and height would make a board with more than 16 digits
+    InvalidShape(usize, usize),
+    /// A row of a CSV grid has this many fields instead of nine
+    WrongFieldCount(usize),
+    /// The CSV field at this row and column (counting from 0) isn't blank or a digit
+    InvalidField(usize, usize),
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use PuzzleError::*;
+        match self {
+            WrongLength(len) => write!(f, "Expected 81 cells but got {}", len),
+            InvalidValue(i, value) => write!(f, "Cell {} has invalid value {}", i, value),
+            NotAPermutation => write!(f, "Digit mapping is not a permutation of 1-9"),
+            InvalidCharacter(c) => write!(f, "Unexpected character {:?}", c),
+            OnLine(line, e) => write!(f, "Line {}: {}", line, e),
+            InvalidRegion(id) => write!(f, "Region {} doesn't have exactly nine cells", id),
+            Conflict(a, b) => write!(f, "Cell {} has the same digit as cell {}", a, b),
+            InvalidNotation(s) => write!(f, "{:?} is not a cell like R4C5", s),
+            InvalidShape(w, h) => write!(f, "Regions can't be {} wide and {} tall", w, h),
+            WrongFieldCount(n) => write!(f, "Expected 9 fields but got {}", n),
+            InvalidField(row, column) => {
+                write!(f, "Field {} of row {} is not a digit", column, row)
+            }
+        }
+    }
+}
+
+impl Error for PuzzleError {}
+
+/// Ways that the set cells of a board can break the rules
+#[derive(Debug)]
+pub enum ValidationError {
+    InvalidRow(usize),
+    InvalidColumn(usize),
+    InvalidBlock(usize),
+    /// The cells at these indexes have the same digit, which a variant's extra rule
+    /// doesn't allow
+    VariantConflict(usize, usize),
+    /// The given at this index is one of the digits forbidden in its cell
+    Forbidden(usize),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ValidationError::*;
+        match self {
+            InvalidRow(i) => write!(f, "Row {} of the puzzle is not valid!", i),
+            InvalidColumn(i) => write!(f, "Column {} of the puzzle is not valid!", i),
+            InvalidBlock(i) => write!(f, "Block {} of the puzzle is not valid!", i),
+            VariantConflict(a, b) => write!(
+                f,
+                "Cells {} and {} can't have the same digit in this variant",
+                a, b
+            ),
+            Forbidden(i) => write!(f, "Cell {} has a digit that is forbidden there", i),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Why a digit can't be placed in a cell
+#[derive(Debug)]
+pub enum PlaceError {
+    /// The peer at this index already has the digit
+    Conflict(usize),
+}
+
+impl Display for PlaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use PlaceError::*;
+        match self {
+            Conflict(i) => write!(f, "Cell {} already has that digit", i),
+        }
+    }
+}
+
+impl Error for PlaceError {}
+
+/// A row, column or block
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum UnitKind {
+    Row,
+    Column,
+    Block,
+}
+
+/// Why a board can't be completed, as found by `Puzzle::find_contradiction`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Contradiction {
+    /// The unset cell at this index has no candidates left
+    NoCandidates(usize),
+    /// The digit can't go in any unset cell of the unit with this kind and number
+    Unplaceable(Cell, UnitKind, usize),
+}
+
 /// Represents a sudoku board. The cells of the board are stored as an 81 element
 /// array of the Cell enum.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Puzzle {
     cells: [Cell; 81],
 }
@@ -79,31 +207,353 @@ impl Puzzle {
         Self { cells }
     }
 
+    /// Like `new`, but fails if any of the givens conflict. The error names the first
+    /// row, column or block found with a repeated digit.
+    pub fn try_new(cells: [Cell; 81]) -> Result<Self, ValidationError> {
+        let puzzle = Self::new(cells);
+        puzzle.validate()?;
+        Ok(puzzle)
+    }
+
+    /// Check that no row, column or block has a repeated digit. Unset cells are
+    /// ignored. The error names the first unit found with a repeat, checking the
+    /// row, column and block with the same index before moving on to the next index.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with(|_| ())
+    }
+
+    /// Like `validate`, but `log` is called with a message for every unit that has a
+    /// repeated digit, not just the first one
+    pub fn validate_with<F: FnMut(&str)>(&self, mut log: F) -> Result<(), ValidationError> {
+        let mut first = None;
+
+        for i in 0..9 {
+            let issues = [
+                (!self.row(i).is_valid()).then_some(ValidationError::InvalidRow(i)),
+                (!self.column(i).is_valid()).then_some(ValidationError::InvalidColumn(i)),
+                (!self.block(i).is_valid()).then_some(ValidationError::InvalidBlock(i)),
+            ];
+
+            for issue in issues.into_iter().flatten() {
+                log(&issue.to_string());
+                first.get_or_insert(issue);
+            }
+        }
+
+        match first {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Build a board from exactly 81 bytes in the range 0-9, where 0 is an unset cell
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PuzzleError> {
+        if bytes.len() != 81 {
+            return Err(PuzzleError::WrongLength(bytes.len()));
+        }
+
+        let mut cells: [Cell; 81] = [Cell::Unset; 81];
+        for (i, byte) in bytes.iter().enumerate() {
+            if *byte > 9 {
+                return Err(PuzzleError::InvalidValue(i, *byte));
+            }
+            cells[i] = Cell::from(*byte);
+        }
+
+        Ok(Self::new(cells))
+    }
+
+    /// The value of every cell as a byte, with 0 for unset cells. This is the simplest
+    /// format to hand to code in other languages.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.cells.iter().map(|&c| u8::from(c)).collect()
+    }
+
+    /// Read a board written by `to_vec`. This is the same as `from_bytes`.
+    pub fn from_vec(v: &[u8]) -> Result<Self, PuzzleError> {
+        Self::from_bytes(v)
+    }
+
+    /// The board as nine rows of nine cells, with `None` for unset cells. This is the
+    /// shape that many table widgets want.
+    pub fn to_option_grid(&self) -> Vec<Vec<Option<u8>>> {
+        self.cells
+            .chunks(9)
+            .map(|row| {
+                row.iter()
+                    .map(|&cell| cell.is_set().then(|| u8::from(cell)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Read a board written by `to_option_grid`. Every row has to have nine cells, and
+    /// every `Some` has to hold one of the digits 1-9.
+    pub fn from_option_grid(grid: &[Vec<Option<u8>>]) -> Result<Self, PuzzleError> {
+        let mut values = Vec::with_capacity(81);
+
+        for (row, cells) in grid.iter().enumerate() {
+            if cells.len() != 9 {
+                return Err(PuzzleError::OnLine(
+                    row,
+                    Box::new(PuzzleError::WrongFieldCount(cells.len())),
+                ));
+            }
+
+            for cell in cells {
+                match *cell {
+                    None => values.push(0),
+                    Some(0) => return Err(PuzzleError::InvalidValue(values.len(), 0)),
+                    Some(digit) => values.push(digit),
+                }
+            }
+        }
+
+        Self::from_bytes(&values)
+    }
+
+    /// The cells block by block instead of row by row: all of block 0 (in the order of
+    /// `block_indexes`), then all of block 1, and so on
+    pub fn to_block_major(&self) -> [Cell; 81] {
+        std::array::from_fn(|i| self.cells[block_indexes(i / 9)[i % 9]])
+    }
+
+    /// Read a board from cells in the order written by `to_block_major`
+    pub fn from_block_major(cells: [Cell; 81]) -> Puzzle {
+        let mut rows = [Cell::Unset; 81];
+        for (i, cell) in cells.into_iter().enumerate() {
+            rows[block_indexes(i / 9)[i % 9]] = cell;
+        }
+
+        Puzzle::new(rows)
+    }
+
+    /// Pack the board into 41 bytes, two cells per byte. The first cell of each pair is
+    /// in the high nibble, and the low nibble of the last byte is unused.
+    pub fn to_packed(&self) -> [u8; 41] {
+        let mut bytes = [0; 41];
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            let value = u8::from(*cell);
+            bytes[i / 2] |= if i % 2 == 0 { value << 4 } else { value };
+        }
+
+        bytes
+    }
+
+    /// Unpack a board stored with `to_packed`
+    pub fn from_packed(bytes: &[u8; 41]) -> Result<Self, PuzzleError> {
+        let values: Vec<u8> = (0..81)
+            .map(|i| {
+                if i % 2 == 0 {
+                    bytes[i / 2] >> 4
+                } else {
+                    bytes[i / 2] & 0x0F
+                }
+            })
+            .collect();
+
+        Self::from_bytes(&values)
+    }
+
+    /// A board with every cell unset
+    pub fn empty() -> Self {
+        Self::new([Cell::Unset; 81])
+    }
+
     pub fn set_cell(&self, index: usize, cell: Cell) -> Self {
         debug_assert!(index < 81);
 
-        let mut cells = self.cells.clone();
+        let mut cells = self.cells;
         cells[index] = cell;
         Puzzle::new(cells)
     }
 
-    // pub fn get_cell(&self, index: usize) -> Cell {
-    //     self.cells[index]
-    // }
+    /// Like calling `set_cell` for each placement in order, but copying the board only
+    /// once. A later placement for the same cell wins.
+    pub fn set_cells(&self, placements: &[(usize, Cell)]) -> Self {
+        let mut cells = self.cells;
+        for &(index, cell) in placements {
+            debug_assert!(index < 81);
+            cells[index] = cell;
+        }
+
+        Puzzle::new(cells)
+    }
+
+    /// Clear a cell back to `Unset`
+    pub fn unset_cell(&self, index: usize) -> Self {
+        self.set_cell(index, Cell::Unset)
+    }
+
+    /// A copy of the board where only the cells in `keep` keep their values, and every
+    /// other cell is unset
+    pub fn mask(&self, keep: &[usize]) -> Self {
+        let mut cells = [Cell::Unset; 81];
+        for &i in keep {
+            debug_assert!(i < 81);
+            cells[i] = self.cells[i];
+        }
+
+        Self::new(cells)
+    }
+
+    /// The cells of `solution` that are unset on this board, with every given of this
+    /// board unset instead. Laid over the givens, this gives back the whole solution.
+    pub fn solution_only(&self, solution: &Puzzle) -> Self {
+        let unset: Vec<usize> = self.iter_unset_cells().map(|(i, _)| i).collect();
+        solution.mask(&unset)
+    }
+
+    pub fn get_cell(&self, index: usize) -> Cell {
+        self.cells[index]
+    }
 
     // Return an iterator over all the set cells in the sudoku grid and their indexes.
     pub fn iter_unset_cells(&self) -> impl Iterator<Item = (usize, &Cell)> {
         self.cells.iter().enumerate().filter(|(_i, c)| !c.is_set())
     }
 
+    /// Iterate over the indexes and values of all the cells that are set
+    pub fn iter_set_cells(&self) -> impl Iterator<Item = (usize, Cell)> {
+        self.cells
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_i, c)| c.is_set())
+    }
+
+    /// The `(row, column)` and value of every set cell, in index order
+    pub fn givens(&self) -> Vec<((usize, usize), Cell)> {
+        self.iter_set_cells()
+            .map(|(i, cell)| ((i / 9, i % 9), cell))
+            .collect()
+    }
+
+    /// The number of cells that are set
+    pub fn clue_count(&self) -> usize {
+        self.iter_set_cells().count()
+    }
+
+    /// How many more times `digit` has to be placed to finish the board. This is 0 once
+    /// the digit is on the board nine times.
+    pub fn remaining_count(&self, digit: Cell) -> usize {
+        debug_assert!(digit.is_set());
+
+        9usize.saturating_sub(self.cells.iter().filter(|&&c| c == digit).count())
+    }
+
+    /// Whether every cell is set. Unlike `is_solved`, this doesn't check that the
+    /// board is valid.
+    pub fn is_complete(&self) -> bool {
+        self.cells.iter().all(|c| c.is_set())
+    }
+
+    /// Every cell that differs between this board and `other`, as its index, its value
+    /// here and its value in `other`
+    pub fn diff(&self, other: &Puzzle) -> Vec<(usize, Cell, Cell)> {
+        self.cells
+            .iter()
+            .zip(other.cells.iter())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(i, (&old, &new))| (i, old, new))
+            .collect()
+    }
+
+    /// Whether both boards have givens in exactly the same cells, whatever the digits
+    /// are. This is a cheap check for grouping puzzles before comparing them properly.
+    pub fn same_clue_pattern(&self, other: &Puzzle) -> bool {
+        self.cells
+            .iter()
+            .zip(other.cells.iter())
+            .all(|(a, b)| a.is_set() == b.is_set())
+    }
+
+    /// A 64-bit FNV-1a hash of the 81 cell values. Unlike the `Hash` impl it doesn't
+    /// depend on a randomly seeded hasher, so it's the same on every run and can be
+    /// stored.
+    pub fn fingerprint(&self) -> u64 {
+        self.cells
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, &cell| {
+                (hash ^ u8::from(cell) as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            })
+    }
+
+    /// How many cells are set here but unset in `original`, for showing how far a
+    /// solve has progressed
+    pub fn cells_filled_since(&self, original: &Puzzle) -> usize {
+        self.cells
+            .iter()
+            .zip(original.cells.iter())
+            .filter(|(now, before)| now.is_set() && !before.is_set())
+            .count()
+    }
+
+    /// Get one of the three horizontal bands of the board, which are rows 0-2, 3-5 and
+    /// 6-8, in row order
+    pub fn band(&self, index: usize) -> [Cell; 27] {
+        assert!(index < 3);
+
+        let mut out = [Cell::Unset; 27];
+        out.copy_from_slice(&self.cells[27 * index..27 * index + 27]);
+        out
+    }
+
+    /// Get one of the three vertical stacks of the board, which are columns 0-2, 3-5
+    /// and 6-8, in row order
+    pub fn stack(&self, index: usize) -> [Cell; 27] {
+        assert!(index < 3);
+
+        std::array::from_fn(|i| self.cells[(i / 3) * 9 + index * 3 + i % 3])
+    }
+
+    /// The cells of a row along with their indexes on the board
+    pub fn row_cells(&self, row: usize) -> [(usize, Cell); 9] {
+        row_indexes(row).map(|i| (i, self.cells[i]))
+    }
+
+    /// The cells of a column along with their indexes on the board
+    pub fn column_cells(&self, column: usize) -> [(usize, Cell); 9] {
+        column_indexes(column).map(|i| (i, self.cells[i]))
+    }
+
+    /// The cells of a block along with their indexes on the board
+    pub fn block_cells(&self, block: usize) -> [(usize, Cell); 9] {
+        block_indexes(block).map(|i| (i, self.cells[i]))
+    }
+
+    /// Iterate over a row of the board without copying it into an array
+    pub fn row_iter(&self, index: usize) -> impl Iterator<Item = Cell> + '_ {
+        debug_assert!(index < 9);
+
+        self.cells[9 * index..9 * index + 9].iter().copied()
+    }
+
+    /// Iterate over a column of the board without copying it into an array
+    pub fn column_iter(&self, index: usize) -> impl Iterator<Item = Cell> + '_ {
+        debug_assert!(index < 9);
+
+        self.cells.iter().skip(index).step_by(9).copied()
+    }
+
+    /// Iterate over one of the 3x3 blocks of the board without copying it into an array
+    pub fn block_iter(&self, index: usize) -> impl Iterator<Item = Cell> + '_ {
+        debug_assert!(index < 9);
+
+        let start = (index / 3) * 27 + (index % 3) * 3;
+        (0..9).map(move |i| self.cells[start + (i / 3) * 9 + i % 3])
+    }
+
     /// Get a column of the sudoku board
     pub fn column(&self, index: usize) -> [Cell; 9] {
         debug_assert!(index < 9);
 
         let mut out: [Cell; 9] = [Cell::Unset; 9];
 
-        for i in 0..9 {
-            out[i] = self.cells[index + i * 9];
+        for (i, value) in out.iter_mut().enumerate() {
+            *value = self.cells[index + i * 9];
         }
 
         out
@@ -118,9 +568,7 @@ impl Puzzle {
         assert!(row.len() == 9);
 
         let mut out: [Cell; 9] = [Cell::Unset; 9];
-        for i in 0..9 {
-            out[i] = row[i];
-        }
+        out.copy_from_slice(row);
         out
     }
 
@@ -128,60 +576,42 @@ impl Puzzle {
     pub fn block(&self, index: usize) -> [Cell; 9] {
         assert!(index < 9);
 
-        // Te index of the first cell in the block
-        let start_index: usize = match index {
-            0..=2 => index * 3,
-            3..=5 => 27 + ((index % 3) * 3),
-            6..=8 => 54 + ((index % 3) * 3),
+        block_indexes(index).map(|i| self.cells[i])
+    }
 
-            // This is unreachable thanks to the assert at the start of the function
-            _ => unreachable!(),
-        };
+    /// Iterate over all nine rows, top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = [Cell; 9]> + '_ {
+        (0..9).map(|i| self.row(i))
+    }
 
-        let row1 = &self.cells[start_index..start_index + 3];
-        let row2 = &self.cells[start_index + 9..start_index + 9 + 3];
-        let row3 = &self.cells[start_index + 18..start_index + 18 + 3];
+    /// Iterate over all nine columns, left to right
+    pub fn columns(&self) -> impl Iterator<Item = [Cell; 9]> + '_ {
+        (0..9).map(|i| self.column(i))
+    }
 
-        debug_assert!(row1.len() == 3);
-        debug_assert!(row2.len() == 3);
-        debug_assert!(row3.len() == 3);
+    /// Iterate over all nine blocks, left to right and then top to bottom
+    pub fn blocks(&self) -> impl Iterator<Item = [Cell; 9]> + '_ {
+        (0..9).map(|i| self.block(i))
+    }
 
-        let mut out: [Cell; 9] = [Cell::Unset; 9];
-        for (i, value) in row1
-            .iter()
-            .chain(row2.iter())
-            .chain(row3.iter())
-            .enumerate()
-        {
-            out[i] = *value;
-        }
+    /// The digits that don't appear in a row yet
+    pub fn missing_in_row(&self, row: usize) -> HashSet<Cell> {
+        missing(self.row(row))
+    }
 
-        out
+    /// The digits that don't appear in a column yet
+    pub fn missing_in_column(&self, column: usize) -> HashSet<Cell> {
+        missing(self.column(column))
+    }
+
+    /// The digits that don't appear in a block yet
+    pub fn missing_in_block(&self, block: usize) -> HashSet<Cell> {
+        missing(self.block(block))
     }
 
     pub fn possibilities(&self, cell_index: usize) -> HashSet<Cell> {
         debug_assert!(cell_index < 81);
 
-        let row_index = cell_index / 9;
-        let column_index = cell_index % 9;
-        // Given a row and column index, figure out what 3x3 block the cell belongs to
-        let block_index = match (row_index, column_index) {
-            // First row of blocks
-            (0..=2, 0..=2) => 0,
-            (0..=2, 3..=5) => 1,
-            (0..=2, 6..=8) => 2,
-            // Second row of blocks
-            (3..=5, 0..=2) => 3,
-            (3..=5, 3..=5) => 4,
-            (3..=5, 6..=8) => 5,
-            // Third row of blocks
-            (6..=8, 0..=2) => 6,
-            (6..=8, 3..=5) => 7,
-            (6..=8, 6..=8) => 8,
-
-            _ => unreachable!(),
-        };
-
         let mut set = HashSet::new();
         set.insert(Cell::One);
         set.insert(Cell::Two);
@@ -193,18 +623,164 @@ impl Puzzle {
         set.insert(Cell::Eight);
         set.insert(Cell::Nine);
 
-        for cell in self
-            .row(row_index)
-            .iter()
-            .chain(self.column(column_index).iter())
-            .chain(self.block(block_index).iter())
-        {
-            set.remove(cell);
+        set.remove(&self.cells[cell_index]);
+        for peer in peers(cell_index) {
+            set.remove(&self.cells[*peer]);
         }
 
         set
     }
 
+    /// The candidate that comes after `current` for the cell at `index`, going back to
+    /// the smallest one after the largest, for cycling a cell through its legal values.
+    /// Whatever the cell holds now is ignored. `Unset` gives the smallest candidate, and
+    /// a cell without any candidates gives `Unset`.
+    pub fn next_candidate(&self, index: usize, current: Cell) -> Cell {
+        let mask = self.unset_cell(index).possibilities_mask(index);
+
+        // The candidates above `current`, or all of them if there are none
+        let above = mask & (ALL_DIGITS << u8::from(current));
+        let next = if above != 0 { above } else { mask };
+
+        match next {
+            0 => Cell::Unset,
+            next => Cell::from(next.trailing_zeros() as u8 + 1),
+        }
+    }
+
+    /// The set cells that share a row, column or block with the cell at `index`, as
+    /// their index and value, in index order. These are the cells that rule out
+    /// candidates for it.
+    pub fn constraints_on(&self, index: usize) -> Vec<(usize, Cell)> {
+        let mut constraints: Vec<(usize, Cell)> = peers(index)
+            .iter()
+            .map(|&peer| (peer, self.cells[peer]))
+            .filter(|(_, cell)| cell.is_set())
+            .collect();
+        constraints.sort_unstable();
+        constraints
+    }
+
+    /// The unset peers of the cell at `index` that would lose `digit` as a candidate if
+    /// it were placed there, in index order
+    pub fn eliminations_for(&self, index: usize, digit: Cell) -> Vec<usize> {
+        let mut eliminated: Vec<usize> = peers(index)
+            .iter()
+            .copied()
+            .filter(|&peer| !self.cells[peer].is_set() && self.possibilities(peer).contains(&digit))
+            .collect();
+        eliminated.sort_unstable();
+        eliminated
+    }
+
+    /// The index of a peer that already holds `digit`, which would make placing it at
+    /// `index` illegal. Returns `None` if the move is legal.
+    pub fn would_conflict(&self, index: usize, digit: Cell) -> Option<usize> {
+        if !digit.is_set() {
+            return None;
+        }
+
+        peers(index)
+            .iter()
+            .copied()
+            .find(|&peer| self.cells[peer] == digit)
+    }
+
+    /// Like `set_cell`, but only if the move is legal (see `would_conflict`)
+    pub fn try_place(&self, index: usize, digit: Cell) -> Result<Puzzle, PlaceError> {
+        match self.would_conflict(index, digit) {
+            Some(peer) => Err(PlaceError::Conflict(peer)),
+            None => Ok(self.set_cell(index, digit)),
+        }
+    }
+
+    /// The possible values of a cell as a bitmask (see `Cell::mask`), which is cheaper
+    /// to compute than `possibilities`
+    pub fn possibilities_mask(&self, cell_index: usize) -> u16 {
+        debug_assert!(cell_index < 81);
+
+        let used = peers(cell_index)
+            .iter()
+            .fold(self.cells[cell_index].mask(), |mask, &peer| {
+                mask | self.cells[peer].mask()
+            });
+        ALL_DIGITS & !used
+    }
+
+    /// For each of the digits 1-9, which of the unit's unset cells can still hold it.
+    /// Bit `n` of a digit's mask stands for the unit's `n`th cell (see `Unit::indexes`).
+    pub fn digit_positions_in_unit(&self, unit: Unit) -> [u16; 9] {
+        let mut positions = [0; 9];
+        for (position, index) in unit.indexes().into_iter().enumerate() {
+            if self.cells[index].is_set() {
+                continue;
+            }
+            for digit in digits(self.possibilities_mask(index)) {
+                positions[u8::from(digit) as usize - 1] |= 1 << position;
+            }
+        }
+
+        positions
+    }
+
+    /// Each candidate of a cell along with how many of its unset peers would lose that
+    /// candidate if it were placed, least constraining first. Ties keep digit order. Set
+    /// cells have no candidates.
+    pub fn candidate_scores(&self, index: usize) -> Vec<(Cell, usize)> {
+        if self.cells[index].is_set() {
+            return Vec::new();
+        }
+
+        let mut scores: Vec<(Cell, usize)> = digits(self.possibilities_mask(index))
+            .map(|digit| {
+                let affected = peers(index)
+                    .iter()
+                    .filter(|&&peer| {
+                        !self.cells[peer].is_set()
+                            && self.possibilities_mask(peer) & digit.mask() != 0
+                    })
+                    .count();
+                (digit, affected)
+            })
+            .collect();
+
+        scores.sort_by_key(|&(_, affected)| affected);
+        scores
+    }
+
+    /// The number of candidates of every unset cell added up. Fewer candidates per
+    /// empty cell usually means an easier position.
+    pub fn total_candidates(&self) -> usize {
+        self.iter_unset_cells()
+            .map(|(i, _)| self.possibilities_mask(i).count_ones() as usize)
+            .sum()
+    }
+
+    /// Every unset cell with exactly two candidates, along with those two digits in
+    /// ascending order. Many advanced techniques start from these cells.
+    pub fn bivalue_cells(&self) -> Vec<(usize, [Cell; 2])> {
+        self.iter_unset_cells()
+            .filter_map(|(i, _)| {
+                let mask = self.possibilities_mask(i);
+                if mask.count_ones() != 2 {
+                    return None;
+                }
+
+                let low = mask.trailing_zeros() as u8 + 1;
+                let high = 16 - mask.leading_zeros() as u8;
+                Some((i, [Cell::from(low), Cell::from(high)]))
+            })
+            .collect()
+    }
+
+    /// The possible values of a cell in ascending order. Unlike `possibilities`, the
+    /// order is the same on every run.
+    pub fn ordered_possibilities(&self, cell_index: usize) -> Vec<Cell> {
+        let mut ordered: Vec<Cell> = self.possibilities(cell_index).into_iter().collect();
+        ordered.sort_unstable();
+        ordered
+    }
+
     pub fn is_solved(&self) -> bool {
         if !self.is_valid() {
             return false;
@@ -218,50 +794,266 @@ impl Puzzle {
 
         true
     }
-}
 
-trait Valid {
-    fn is_valid(&self) -> bool;
-}
+    /// Whether the board is completely filled in without breaking any rules. This is the
+    /// same as `is_solved`, for callers that want to make it clear which check they mean.
+    pub fn is_filled_and_valid(&self) -> bool {
+        self.is_solved()
+    }
+
+    /// Whether no row, column or block has a repeated digit. Unset cells are ignored,
+    /// so a partially filled board can be consistent.
+    pub fn is_consistent(&self) -> bool {
+        self.rows()
+            .chain(self.columns())
+            .chain(self.blocks())
+            .all(|unit| unit.is_valid())
+    }
+
+    /// Whether the board obviously can't be completed: an unset cell has no candidates
+    /// left, or a row, column or block is missing a digit that none of its unset cells
+    /// can take. Unlike `is_consistent`, this finds problems before any digit appears
+    /// twice.
+    pub fn has_contradiction(&self) -> bool {
+        self.find_contradiction().is_some()
+    }
+
+    /// The first obstruction that `has_contradiction` looks for
+    pub(crate) fn find_contradiction(&self) -> Option<Contradiction> {
+        let masks: Vec<u16> = (0..81).map(|i| self.possibilities_mask(i)).collect();
+
+        if let Some((i, _)) = self.iter_unset_cells().find(|&(i, _)| masks[i] == 0) {
+            return Some(Contradiction::NoCandidates(i));
+        }
 
-impl Valid for Puzzle {
-    /// Validate that the values of the cells are all valid
-    fn is_valid(&self) -> bool {
         for i in 0..9 {
-            if !self.row(i).is_valid() {
-                println!("Row {i} is invalid");
-                return false;
+            let units = [
+                (UnitKind::Row, row_indexes(i)),
+                (UnitKind::Column, column_indexes(i)),
+                (UnitKind::Block, block_indexes(i)),
+            ];
+            for (kind, unit) in units {
+                let present = unit.iter().fold(0, |mask, &i| mask | self.cells[i].mask());
+                let placeable = unit
+                    .iter()
+                    .filter(|&&i| !self.cells[i].is_set())
+                    .fold(0, |mask, &i| mask | masks[i]);
+
+                let missing = ALL_DIGITS & !(present | placeable);
+                if missing != 0 {
+                    let digit = Cell::from(missing.trailing_zeros() as u8 + 1);
+                    return Some(Contradiction::Unplaceable(digit, kind, i));
+                }
             }
-            if !self.column(i).is_valid() {
-                println!("Column {i} is invalid");
-                return false;
+        }
+
+        None
+    }
+
+    /// Every pair of cell indexes that share a row, column, or block and hold the same
+    /// digit. Each pair is ordered `(lower, higher)` and the list is sorted.
+    pub fn all_conflicts(&self) -> Vec<(usize, usize)> {
+        let mut conflicts = Vec::new();
+
+        for i in 0..81 {
+            if !self.cells[i].is_set() {
+                continue;
             }
-            if !self.block(i).is_valid() {
-                println!("Block {i} is invalid");
-                return false;
+
+            for j in (i + 1)..81 {
+                if self.cells[i] == self.cells[j] && shares_unit(i, j) {
+                    conflicts.push((i, j));
+                }
             }
         }
 
-        true
+        conflicts
     }
-}
 
-impl Valid for [Cell; 9] {
-    fn is_valid(&self) -> bool {
-        let mut set: HashSet<Cell> = HashSet::new();
+    /// Build a new board where the cell at `(row, column)` is taken from this board's
+    /// cell at `source(row, column)`
+    fn remap<F>(&self, source: F) -> Self
+    where
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        Self::new(std::array::from_fn(|i| {
+            let (row, column) = source(i / 9, i % 9);
+            self.cells[row * 9 + column]
+        }))
+    }
 
-        for cell in self.iter().filter(|c| c.is_set()) {
-            if set.contains(cell) {
-                return false;
-            }
-            set.insert(*cell);
+    /// Rotate the board 90 degrees clockwise
+    pub fn rotate90(&self) -> Self {
+        self.remap(|row, column| (8 - column, row))
+    }
+
+    /// Swap the board's rows and columns, mirroring it along the main diagonal
+    pub fn transpose(&self) -> Self {
+        self.remap(|row, column| (column, row))
+    }
+
+    /// Mirror the board left to right
+    pub fn reflect_horizontal(&self) -> Self {
+        self.remap(|row, column| (row, 8 - column))
+    }
+
+    /// Replace every digit `n` on the board with `mapping[n - 1]`. Unset cells are left
+    /// alone. `mapping` must contain each of the digits 1-9 exactly once.
+    pub fn relabel(&self, mapping: [Cell; 9]) -> Result<Self, PuzzleError> {
+        let used = mapping.iter().fold(0, |mask, digit| mask | digit.mask());
+        if used != ALL_DIGITS {
+            return Err(PuzzleError::NotAPermutation);
         }
 
-        true
+        let cells = self.cells.map(|cell| match u8::from(cell) {
+            0 => Cell::Unset,
+            digit => mapping[digit as usize - 1],
+        });
+
+        Ok(Self::new(cells))
     }
-}
 
-#[test]
+    /// A random puzzle that is equivalent to this one, made by shuffling the bands,
+    /// stacks, rows within each band and columns within each stack, maybe transposing,
+    /// and relabeling the digits. The same seed always gives the same puzzle. The result
+    /// has the same number of solutions and canonicalizes to the same board.
+    pub fn scramble(&self, seed: u64) -> Self {
+        let mut rng = SplitMix64::new(seed);
+        let mut shuffled_lines = || -> [usize; 9] {
+            let mut groups = [0, 1, 2];
+            rng.shuffle(&mut groups);
+            let mut within = [[0, 1, 2]; 3];
+            for lines in within.iter_mut() {
+                rng.shuffle(lines);
+            }
+            std::array::from_fn(|i| groups[i / 3] * 3 + within[i / 3][i % 3])
+        };
+        let rows = shuffled_lines();
+        let columns = shuffled_lines();
+
+        let mut scrambled = self.remap(|row, column| (rows[row], columns[column]));
+        if rng.below(2) == 1 {
+            scrambled = scrambled.transpose();
+        }
+
+        let mut mapping: [Cell; 9] = std::array::from_fn(|i| Cell::from(i as u8 + 1));
+        rng.shuffle(&mut mapping);
+        match scrambled.relabel(mapping) {
+            Ok(relabeled) => relabeled,
+            Err(_) => unreachable!("a shuffle of the digits 1-9 is a permutation"),
+        }
+    }
+}
+
+/// Build a board from exactly 81 values in the range 0-9 (see `Puzzle::from_bytes`)
+impl TryFrom<&[u8]> for Puzzle {
+    type Error = PuzzleError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Build a board from exactly 81 values in the range 0-9 (see `Puzzle::from_bytes`)
+impl TryFrom<Vec<u8>> for Puzzle {
+    type Error = PuzzleError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// The digits 1-9 that aren't in a unit
+fn missing(unit: [Cell; 9]) -> HashSet<Cell> {
+    (1..=9)
+        .map(Cell::from)
+        .filter(|digit| !unit.contains(digit))
+        .collect()
+}
+
+/// Build a board from nine rows of nine values in the range 0-9, which reads like the
+/// grid itself when written out as a literal
+impl TryFrom<[[u8; 9]; 9]> for Puzzle {
+    type Error = PuzzleError;
+
+    fn try_from(rows: [[u8; 9]; 9]) -> Result<Self, Self::Error> {
+        Self::from_bytes(rows.as_flattened())
+    }
+}
+
+/// Compare a board to a plain array of its cells
+impl PartialEq<[Cell; 81]> for Puzzle {
+    fn eq(&self, other: &[Cell; 81]) -> bool {
+        self.cells == *other
+    }
+}
+
+impl Default for Puzzle {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Cell {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Cell::from(u.int_in_range(0..=9)?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        u8::size_hint(depth)
+    }
+}
+
+/// Any board at all, including ones that break the rules, so that fuzzers reach the
+/// error paths too. Once the input runs out the rest of the cells are unset.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Puzzle {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let cells: [Cell; 81] = u.arbitrary()?;
+        Ok(Puzzle::new(cells))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[Cell; 81]>::size_hint(depth)
+    }
+}
+
+/// Whether a unit holds each of the digits 1-9 exactly once. Unlike `is_valid`, unset
+/// cells aren't allowed.
+pub fn is_complete_unit(unit: &[Cell; 9]) -> bool {
+    // Nine set cells can only cover all nine digits if none of them repeat
+    unit.iter().all(|c| c.is_set()) && unit.iter().fold(0, |mask, c| mask | c.mask()) == ALL_DIGITS
+}
+
+trait Valid {
+    fn is_valid(&self) -> bool;
+}
+
+impl Valid for Puzzle {
+    /// Validate that the values of the cells are all valid. Use `validate_with` to find
+    /// out what's wrong.
+    fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl Valid for [Cell; 9] {
+    fn is_valid(&self) -> bool {
+        let mut set: HashSet<Cell> = HashSet::new();
+
+        for cell in self.iter().filter(|c| c.is_set()) {
+            if set.contains(cell) {
+                return false;
+            }
+            set.insert(*cell);
+        }
+
+        true
+    }
+}
+
+#[test]
 fn test_group_is_valid() {
     let group: [Cell; 9] = [
         Cell::One,
@@ -373,3 +1165,1042 @@ fn test_puzzle_is_valid() {
 
     assert!(puzzle.is_valid());
 }
+
+#[test]
+fn test_all_conflicts() {
+    let mut grid: [Cell; 81] = [Cell::Unset; 81];
+    // Two fives in row 0
+    grid[1] = Cell::Five;
+    grid[7] = Cell::Five;
+    // Two twos in block 8 (which also share column 7)
+    grid[61] = Cell::Two;
+    grid[79] = Cell::Two;
+    // Not a conflict: same digit, but no shared unit
+    grid[40] = Cell::Five;
+
+    let puzzle = Puzzle::new(grid);
+    assert_eq!(puzzle.all_conflicts(), vec![(1, 7), (61, 79)]);
+
+    assert!(Puzzle::new([Cell::Unset; 81]).all_conflicts().is_empty());
+}
+
+#[test]
+fn test_empty_puzzle() {
+    assert_eq!(Puzzle::empty().iter_unset_cells().count(), 81);
+    assert_eq!(Puzzle::default().iter_unset_cells().count(), 81);
+}
+
+#[test]
+fn test_from_bytes() {
+    #[rustfmt::skip]
+    let bytes = [
+        5, 3, 0, 0, 7, 0, 0, 0, 0,
+        6, 0, 0, 1, 9, 5, 0, 0, 0,
+        0, 9, 8, 0, 0, 0, 0, 6, 0,
+        8, 0, 0, 0, 6, 0, 0, 0, 3,
+        4, 0, 0, 8, 0, 3, 0, 0, 1,
+        7, 0, 0, 0, 2, 0, 0, 0, 6,
+        0, 6, 0, 0, 0, 0, 2, 8, 0,
+        0, 0, 0, 4, 1, 9, 0, 0, 5,
+        0, 0, 0, 0, 8, 0, 0, 7, 9
+    ];
+
+    let puzzle = Puzzle::from_bytes(&bytes).ok().unwrap();
+    assert_eq!(puzzle.get_cell(0), Cell::Five);
+    assert_eq!(puzzle.get_cell(2), Cell::Unset);
+    assert_eq!(puzzle.get_cell(80), Cell::Nine);
+
+    assert!(matches!(
+        Puzzle::from_bytes(&bytes[..80]),
+        Err(PuzzleError::WrongLength(80))
+    ));
+
+    let mut bytes = bytes;
+    bytes[10] = 12;
+    assert!(matches!(
+        Puzzle::from_bytes(&bytes),
+        Err(PuzzleError::InvalidValue(10, 12))
+    ));
+}
+
+#[test]
+fn test_iter_set_cells() {
+    let puzzle = crate::testing::sample();
+
+    let set: Vec<(usize, Cell)> = puzzle.iter_set_cells().collect();
+    assert_eq!(set.len(), 30);
+    assert_eq!(set[0], (0, Cell::Five));
+    assert_eq!(set[2], (4, Cell::Seven));
+    assert_eq!(set.len() + puzzle.iter_unset_cells().count(), 81);
+}
+
+#[test]
+fn test_clue_count() {
+    let puzzle = crate::testing::sample();
+
+    assert_eq!(puzzle.clue_count(), 30);
+    assert!(!puzzle.is_complete());
+    assert_eq!(Puzzle::empty().clue_count(), 0);
+
+    // Complete, but not valid
+    let full = Puzzle::new([Cell::One; 81]);
+    assert!(full.is_complete());
+    assert!(!full.is_solved());
+}
+
+#[test]
+fn test_unset_cell() {
+    let puzzle = Puzzle::empty().set_cell(40, Cell::Seven);
+    assert_eq!(puzzle.get_cell(40), Cell::Seven);
+
+    let puzzle = puzzle.unset_cell(40);
+    assert_eq!(puzzle.get_cell(40), Cell::Unset);
+    assert_eq!(puzzle.clue_count(), 0);
+}
+
+#[test]
+fn test_transformations() {
+    let puzzle = crate::testing::sample();
+
+    let rotated = puzzle.rotate90();
+    assert_ne!(rotated, puzzle);
+    // The left column becomes the top row, read from the bottom up
+    let mut left_column = puzzle.column(0);
+    left_column.reverse();
+    assert_eq!(rotated.row(0), left_column);
+    assert_eq!(rotated.rotate90().rotate90().rotate90(), puzzle);
+
+    assert_eq!(puzzle.transpose().row(3), puzzle.column(3));
+    assert_eq!(puzzle.transpose().transpose(), puzzle);
+
+    let mut top_row = puzzle.row(0);
+    top_row.reverse();
+    assert_eq!(puzzle.reflect_horizontal().row(0), top_row);
+    assert_eq!(puzzle.reflect_horizontal().reflect_horizontal(), puzzle);
+
+    // Transforms preserve validity
+    assert!(rotated.all_conflicts().is_empty());
+    assert!(puzzle.transpose().all_conflicts().is_empty());
+    assert!(puzzle.reflect_horizontal().all_conflicts().is_empty());
+}
+
+#[test]
+fn test_relabel() {
+    let puzzle = crate::testing::sample();
+
+    use Cell::*;
+    let identity = [One, Two, Three, Four, Five, Six, Seven, Eight, Nine];
+    assert_eq!(puzzle.relabel(identity).ok(), Some(puzzle.clone()));
+
+    // Swap threes and fives
+    let swap = [One, Two, Five, Four, Three, Six, Seven, Eight, Nine];
+    let swapped = puzzle.relabel(swap).ok().unwrap();
+    assert_eq!(swapped.get_cell(0), Three);
+    assert_eq!(swapped.get_cell(1), Five);
+    assert_eq!(swapped.get_cell(2), Unset);
+    assert_eq!(swapped.relabel(swap).ok(), Some(puzzle.clone()));
+
+    let repeated = [One, One, Three, Four, Five, Six, Seven, Eight, Nine];
+    assert!(matches!(
+        puzzle.relabel(repeated),
+        Err(PuzzleError::NotAPermutation)
+    ));
+    let with_unset = [Unset, Two, Three, Four, Five, Six, Seven, Eight, Nine];
+    assert!(matches!(
+        puzzle.relabel(with_unset),
+        Err(PuzzleError::NotAPermutation)
+    ));
+}
+
+#[test]
+fn test_packed() {
+    let puzzle = crate::testing::sample();
+
+    let packed = puzzle.to_packed();
+    assert_eq!(packed[0], 0x53);
+    assert_eq!(packed[40], 0x90);
+
+    for board in [puzzle, Puzzle::empty(), Puzzle::new([Cell::Nine; 81])] {
+        assert_eq!(Puzzle::from_packed(&board.to_packed()).ok(), Some(board));
+    }
+
+    let mut packed = [0; 41];
+    packed[3] = 0x0C;
+    assert!(matches!(
+        Puzzle::from_packed(&packed),
+        Err(PuzzleError::InvalidValue(7, 12))
+    ));
+}
+
+#[test]
+fn test_puzzle_hash() {
+    let mut set: HashSet<Puzzle> = HashSet::new();
+    set.insert(Puzzle::empty().set_cell(3, Cell::Four));
+    set.insert(Puzzle::empty().set_cell(3, Cell::Four));
+    set.insert(Puzzle::empty().set_cell(3, Cell::Five));
+
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn test_ordered_possibilities() {
+    let puzzle = crate::testing::sample();
+
+    for (i, _) in puzzle.iter_unset_cells() {
+        let ordered = puzzle.ordered_possibilities(i);
+        let values: Vec<u8> = ordered.iter().map(|c| u8::from(*c)).collect();
+
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(ordered.len(), puzzle.possibilities(i).len());
+        assert!(ordered.iter().all(|c| puzzle.possibilities(i).contains(c)));
+    }
+
+    assert_eq!(
+        puzzle.ordered_possibilities(50),
+        vec![Cell::One, Cell::Four]
+    );
+}
+
+#[test]
+fn test_cell_ordering() {
+    use crate::rng::SplitMix64;
+
+    let sorted: Vec<Cell> = (0..=9).map(Cell::from).collect();
+
+    let mut cells = sorted.clone();
+    SplitMix64::new(3).shuffle(&mut cells);
+    cells.sort();
+
+    assert_eq!(cells, sorted);
+    assert!(Cell::Unset < Cell::One);
+    assert!(Cell::Eight < Cell::Nine);
+}
+
+#[test]
+fn test_units() {
+    let puzzle = crate::testing::sample();
+
+    assert_eq!(puzzle.rows().count(), 9);
+    assert_eq!(puzzle.columns().count(), 9);
+    assert_eq!(puzzle.blocks().count(), 9);
+
+    assert_eq!(puzzle.blocks().next(), Some(puzzle.block(0)));
+    assert_eq!(puzzle.rows().nth(4), Some(puzzle.row(4)));
+    assert_eq!(puzzle.columns().last(), Some(puzzle.column(8)));
+}
+
+#[test]
+fn test_diff() {
+    use crate::solver::solve_sudoku;
+
+    let puzzle = crate::testing::sample();
+    let solution = solve_sudoku(&puzzle).ok().unwrap();
+
+    let diff = puzzle.diff(&solution);
+    assert_eq!(diff.len(), 81 - puzzle.clue_count());
+    assert!(
+        diff.iter()
+            .map(|&(i, _, _)| i)
+            .eq(puzzle.iter_unset_cells().map(|(i, _)| i))
+    );
+    assert!(
+        diff.iter()
+            .all(|&(i, old, new)| old == Cell::Unset && new == solution.get_cell(i))
+    );
+
+    assert!(puzzle.diff(&puzzle).is_empty());
+    assert_eq!(
+        puzzle.diff(&puzzle.set_cell(0, Cell::Two)),
+        vec![(0, Cell::Five, Cell::Two)]
+    );
+}
+
+#[test]
+fn test_try_new() {
+    let puzzle = crate::testing::sample();
+    assert_eq!(Puzzle::try_new(puzzle.cells).ok(), Some(puzzle.clone()));
+
+    // A second 5 in the first row, in a different column and block
+    let cells = puzzle.set_cell(8, Cell::Five).cells;
+    assert!(matches!(
+        Puzzle::try_new(cells),
+        Err(ValidationError::InvalidRow(0))
+    ));
+
+    // A second 5 in the first column
+    let cells = puzzle.set_cell(72, Cell::Five).cells;
+    assert!(matches!(
+        Puzzle::try_new(cells),
+        Err(ValidationError::InvalidColumn(0))
+    ));
+
+    // A second 5 in the first block only
+    let cells = puzzle.set_cell(20, Cell::Five).cells;
+    assert!(matches!(
+        Puzzle::try_new(cells),
+        Err(ValidationError::InvalidBlock(0))
+    ));
+}
+
+#[test]
+fn test_constraints_on() {
+    let puzzle = crate::testing::sample();
+
+    // Cell 40 can only be a 5
+    assert_eq!(puzzle.ordered_possibilities(40), vec![Cell::Five]);
+
+    let constraints = puzzle.constraints_on(40);
+    assert!(constraints.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert!(constraints.iter().all(|&(i, cell)| {
+        peers(40).contains(&i) && cell.is_set() && puzzle.get_cell(i) == cell
+    }));
+
+    let ruled_out: HashSet<Cell> = constraints.iter().map(|&(_, cell)| cell).collect();
+    assert_eq!(ruled_out.len(), 8);
+    assert!(!ruled_out.contains(&Cell::Five));
+
+    assert!(Puzzle::empty().constraints_on(0).is_empty());
+}
+
+#[test]
+fn test_is_consistent() {
+    let puzzle = crate::testing::sample();
+    assert!(puzzle.is_consistent());
+    assert!(!puzzle.is_solved());
+    assert!(!puzzle.is_filled_and_valid());
+
+    assert!(!puzzle.set_cell(2, Cell::Five).is_consistent());
+    assert!(Puzzle::empty().is_consistent());
+
+    let solved = crate::testing::sample_solution();
+    assert!(solved.is_consistent());
+    assert!(solved.is_filled_and_valid());
+}
+
+#[test]
+fn test_mask() {
+    let solved = crate::testing::sample_solution();
+
+    assert_eq!(solved.mask(&[]), Puzzle::empty());
+
+    let all: Vec<usize> = (0..81).collect();
+    assert_eq!(solved.mask(&all), solved);
+
+    let masked = solved.mask(&[0, 40, 80]);
+    assert_eq!(masked.clue_count(), 3);
+    assert_eq!(masked.get_cell(40), Cell::Five);
+    assert_eq!(masked.get_cell(80), Cell::Nine);
+}
+
+#[test]
+fn test_missing_in_units() {
+    let puzzle = crate::testing::sample();
+
+    let digits =
+        |values: &[u8]| -> HashSet<Cell> { values.iter().map(|&v| Cell::from(v)).collect() };
+
+    // Row 0 is 5 3 . . 7 . . . .
+    assert_eq!(puzzle.missing_in_row(0), digits(&[1, 2, 4, 6, 8, 9]));
+    // Column 4 is 7 9 . 6 . 2 . 1 8
+    assert_eq!(puzzle.missing_in_column(4), digits(&[3, 4, 5]));
+    // Block 4 is . 6 . 8 . 3 . 2 .
+    assert_eq!(puzzle.missing_in_block(4), digits(&[1, 4, 5, 7, 9]));
+
+    // Every digit that can go in a cell is missing from all three of its units
+    for (i, _) in puzzle.iter_unset_cells() {
+        let (row, column, block) = crate::geometry::coords(i);
+        let missing: HashSet<Cell> = puzzle
+            .missing_in_row(row)
+            .intersection(&puzzle.missing_in_column(column))
+            .copied()
+            .collect::<HashSet<Cell>>()
+            .intersection(&puzzle.missing_in_block(block))
+            .copied()
+            .collect();
+        assert_eq!(missing, puzzle.possibilities(i));
+    }
+
+    assert_eq!(Puzzle::empty().missing_in_row(3).len(), 9);
+}
+
+#[test]
+fn test_try_from_bytes() {
+    let values: Vec<u8> =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .bytes()
+            .map(|c| c - b'0')
+            .collect();
+
+    let puzzle = Puzzle::try_from(values.clone()).ok().unwrap();
+    assert_eq!(puzzle.get_cell(0), Cell::Five);
+    assert_eq!(Puzzle::try_from(values.as_slice()).ok(), Some(puzzle));
+
+    assert!(matches!(
+        Puzzle::try_from(values[..80].to_vec()),
+        Err(PuzzleError::WrongLength(80))
+    ));
+
+    let mut out_of_range = values;
+    out_of_range[3] = 10;
+    assert!(matches!(
+        Puzzle::try_from(out_of_range),
+        Err(PuzzleError::InvalidValue(3, 10))
+    ));
+}
+
+#[test]
+fn test_validate() {
+    let puzzle = crate::testing::sample();
+    assert!(puzzle.validate().is_ok());
+    assert!(Puzzle::empty().validate().is_ok());
+
+    // A second 3 in row 4, away from the other 3's column and block
+    assert!(matches!(
+        puzzle.set_cell(38, Cell::Three).validate(),
+        Err(ValidationError::InvalidRow(4))
+    ));
+    // A second 1 in column 8
+    assert!(matches!(
+        puzzle.set_cell(8, Cell::One).validate(),
+        Err(ValidationError::InvalidColumn(8))
+    ));
+    // A second 5 in block 8
+    assert!(matches!(
+        puzzle.set_cell(78, Cell::Five).validate(),
+        Err(ValidationError::InvalidBlock(8))
+    ));
+}
+
+#[test]
+fn test_eliminations_for() {
+    // Every peer of an empty cell on an empty board still has every candidate
+    let mut expected = peers(40).to_vec();
+    expected.sort_unstable();
+    assert_eq!(Puzzle::empty().eliminations_for(40, Cell::Three), expected);
+
+    let puzzle = crate::testing::sample();
+
+    // Placing a 4 in cell 2 only affects the unset peers that could still be a 4
+    let eliminated = puzzle.eliminations_for(2, Cell::Four);
+    assert!(eliminated.iter().all(|&i| {
+        peers(2).contains(&i)
+            && !puzzle.get_cell(i).is_set()
+            && puzzle.possibilities(i).contains(&Cell::Four)
+    }));
+    assert_eq!(eliminated, vec![5, 6, 7, 8, 10, 11, 56, 74]);
+    let placed = puzzle.set_cell(2, Cell::Four);
+    assert!(
+        eliminated
+            .iter()
+            .all(|&i| !placed.possibilities(i).contains(&Cell::Four))
+    );
+}
+
+#[test]
+fn test_possibilities_mask() {
+    let puzzle = crate::testing::sample();
+
+    for i in 0..81 {
+        let mask = puzzle.possibilities_mask(i);
+        let expected = puzzle
+            .possibilities(i)
+            .iter()
+            .fold(0, |mask, digit| mask | digit.mask());
+        assert_eq!(mask, expected);
+    }
+}
+
+#[test]
+fn test_eq_array() {
+    #[rustfmt::skip]
+    let bytes: [u8; 81] = [
+        5, 3, 0, 0, 7, 0, 0, 0, 0,
+        6, 0, 0, 1, 9, 5, 0, 0, 0,
+        0, 9, 8, 0, 0, 0, 0, 6, 0,
+        8, 0, 0, 0, 6, 0, 0, 0, 3,
+        4, 0, 0, 8, 0, 3, 0, 0, 1,
+        7, 0, 0, 0, 2, 0, 0, 0, 6,
+        0, 6, 0, 0, 0, 0, 2, 8, 0,
+        0, 0, 0, 4, 1, 9, 0, 0, 5,
+        0, 0, 0, 0, 8, 0, 0, 7, 9,
+    ];
+    let cells: [Cell; 81] = bytes.map(Cell::from);
+
+    let puzzle = Puzzle::new(cells);
+    assert_eq!(puzzle, cells);
+    assert_ne!(puzzle.set_cell(2, Cell::Four), cells);
+}
+
+#[test]
+fn test_cells_filled_since() {
+    use crate::solver::solve_sudoku;
+
+    let puzzle = crate::testing::sample();
+    let solution = solve_sudoku(&puzzle).ok().unwrap();
+
+    assert_eq!(
+        solution.cells_filled_since(&puzzle),
+        81 - puzzle.clue_count()
+    );
+    assert_eq!(puzzle.cells_filled_since(&puzzle), 0);
+    // Changing a clue doesn't count as filling a cell
+    assert_eq!(puzzle.set_cell(0, Cell::One).cells_filled_since(&puzzle), 0);
+    assert_eq!(
+        puzzle.set_cell(2, Cell::Four).cells_filled_since(&puzzle),
+        1
+    );
+}
+
+#[test]
+fn test_remaining_count() {
+    let puzzle = crate::testing::sample();
+
+    let remaining: Vec<usize> = (1..=9)
+        .map(|digit| puzzle.remaining_count(Cell::from(digit)))
+        .collect();
+    assert_eq!(remaining, vec![6, 7, 6, 7, 6, 4, 6, 4, 5]);
+    assert_eq!(remaining.iter().sum::<usize>(), 81 - puzzle.clue_count());
+
+    let solution = crate::solver::solve_sudoku(&puzzle).ok().unwrap();
+    assert!((1..=9).all(|digit| solution.remaining_count(Cell::from(digit)) == 0));
+}
+
+#[test]
+fn test_bivalue_cells() {
+    // The first row is missing only 8 and 9. The first column already has an 8, so the
+    // corner can only be 9, leaving cell 1 as the only cell with two candidates.
+    #[rustfmt::skip]
+    let puzzle = Puzzle::from_bytes(&[
+        0, 0, 1, 2, 3, 4, 5, 6, 7,
+        1, 0, 0, 0, 0, 0, 0, 0, 0,
+        2, 0, 0, 0, 0, 0, 0, 0, 0,
+        3, 0, 0, 0, 0, 0, 0, 0, 0,
+        4, 0, 0, 0, 0, 0, 0, 0, 0,
+        5, 0, 0, 0, 0, 0, 0, 0, 0,
+        6, 0, 0, 0, 0, 0, 0, 0, 0,
+        7, 0, 0, 0, 0, 0, 0, 0, 0,
+        8, 0, 0, 0, 0, 0, 0, 0, 0,
+    ]).ok().unwrap();
+
+    assert_eq!(puzzle.bivalue_cells(), vec![(1, [Cell::Eight, Cell::Nine])]);
+
+    assert_eq!(puzzle.possibilities_mask(0), Cell::Nine.mask());
+    assert!(Puzzle::empty().bivalue_cells().is_empty());
+}
+
+#[test]
+fn test_would_conflict() {
+    let puzzle = crate::testing::sample();
+
+    // The 7 in cell 4 is in the same row as cell 2
+    assert_eq!(puzzle.would_conflict(2, Cell::Seven), Some(4));
+    // The 8 in cell 20 is in the same block
+    assert_eq!(puzzle.would_conflict(2, Cell::Eight), Some(20));
+    assert_eq!(puzzle.would_conflict(2, Cell::Four), None);
+    assert_eq!(puzzle.would_conflict(2, Cell::Unset), None);
+}
+
+#[test]
+fn test_unit_iters() {
+    let puzzle = crate::testing::sample();
+
+    assert_eq!(
+        puzzle.column_iter(8).collect::<Vec<_>>(),
+        puzzle.column(8).to_vec()
+    );
+    for i in 0..9 {
+        assert!(puzzle.row_iter(i).eq(puzzle.row(i)));
+        assert!(puzzle.column_iter(i).eq(puzzle.column(i)));
+        assert!(puzzle.block_iter(i).eq(puzzle.block(i)));
+    }
+}
+
+#[test]
+fn test_to_vec() {
+    let puzzle = crate::testing::sample();
+
+    let bytes = puzzle.to_vec();
+    assert_eq!(bytes.len(), 81);
+    assert_eq!(&bytes[..5], &[5, 3, 0, 0, 7]);
+    assert_eq!(Puzzle::from_vec(&bytes).ok(), Some(puzzle));
+
+    assert!(Puzzle::from_vec(&bytes[1..]).is_err());
+}
+
+#[test]
+fn test_has_contradiction() {
+    let puzzle = crate::testing::sample();
+    assert!(!puzzle.has_contradiction());
+    assert!(!Puzzle::empty().has_contradiction());
+
+    // Cell 0 has no candidates left
+    let mut cells = [Cell::Unset; 81];
+    for digit in 1..=8 {
+        cells[digit as usize] = Cell::from(digit);
+    }
+    cells[9] = Cell::Nine;
+    let stuck = Puzzle::new(cells);
+    assert!(stuck.is_consistent());
+    assert!(stuck.has_contradiction());
+
+    // Every cell has candidates, but 1 can't go anywhere in the first row
+    let mut cells = [Cell::Unset; 81];
+    cells[0] = Cell::Two;
+    cells[1] = Cell::Three;
+    for i in [12, 24, 38] {
+        cells[i] = Cell::One;
+    }
+    let stuck = Puzzle::new(cells);
+    assert!(stuck.is_consistent());
+    assert!(
+        stuck
+            .iter_unset_cells()
+            .all(|(i, _)| stuck.possibilities_mask(i) != 0)
+    );
+    assert!(stuck.has_contradiction());
+}
+
+#[test]
+fn test_bands_and_stacks() {
+    let puzzle = crate::testing::sample();
+
+    for band in 0..3 {
+        let rows: Vec<Cell> = (0..3).flat_map(|r| puzzle.row(band * 3 + r)).collect();
+        assert_eq!(puzzle.band(band).to_vec(), rows);
+    }
+
+    // A stack is made of the same cells as the band of the transposed board, but the
+    // cells are in row order
+    for stack in 0..3 {
+        let mut cells = puzzle.stack(stack).to_vec();
+        let mut transposed = puzzle.transpose().band(stack).to_vec();
+        cells.sort_unstable();
+        transposed.sort_unstable();
+        assert_eq!(cells, transposed);
+    }
+    assert_eq!(
+        &puzzle.stack(0)[..6],
+        &[
+            Cell::Five,
+            Cell::Three,
+            Cell::Unset,
+            Cell::Six,
+            Cell::Unset,
+            Cell::Unset
+        ]
+    );
+}
+
+#[test]
+fn test_same_clue_pattern() {
+    let puzzle = crate::testing::sample();
+    let mapping = [9, 8, 7, 6, 5, 4, 3, 2, 1].map(Cell::from);
+    let relabeled = puzzle.relabel(mapping).ok().unwrap();
+
+    assert_ne!(puzzle, relabeled);
+    assert!(puzzle.same_clue_pattern(&relabeled));
+    assert!(puzzle.same_clue_pattern(&puzzle));
+    assert!(!puzzle.same_clue_pattern(&puzzle.unset_cell(0)));
+    assert!(!puzzle.same_clue_pattern(&puzzle.transpose()));
+}
+
+#[test]
+fn test_try_from_rows() {
+    let rows = [
+        [5, 3, 0, 0, 7, 0, 0, 0, 0],
+        [6, 0, 0, 1, 9, 5, 0, 0, 0],
+        [0, 9, 8, 0, 0, 0, 0, 6, 0],
+        [8, 0, 0, 0, 6, 0, 0, 0, 3],
+        [4, 0, 0, 8, 0, 3, 0, 0, 1],
+        [7, 0, 0, 0, 2, 0, 0, 0, 6],
+        [0, 6, 0, 0, 0, 0, 2, 8, 0],
+        [0, 0, 0, 4, 1, 9, 0, 0, 5],
+        [0, 0, 0, 0, 8, 0, 0, 7, 9],
+    ];
+    let puzzle = Puzzle::try_from(rows).ok().unwrap();
+    assert_eq!(puzzle, crate::testing::sample());
+
+    let mut invalid = rows;
+    invalid[1][2] = 10;
+    assert!(matches!(
+        Puzzle::try_from(invalid),
+        Err(PuzzleError::InvalidValue(11, 10))
+    ));
+}
+
+#[test]
+fn test_total_candidates() {
+    let solution = crate::testing::sample_solution();
+
+    assert_eq!(Puzzle::empty().total_candidates(), 81 * 9);
+    assert_eq!(solution.total_candidates(), 0);
+
+    // Adding givens from the solution only ever takes candidates away
+    let mut puzzle = Puzzle::empty();
+    let mut total = puzzle.total_candidates();
+    for i in (0..81).step_by(4) {
+        puzzle = puzzle.set_cell(i, solution.get_cell(i));
+        assert!(puzzle.total_candidates() < total);
+        total = puzzle.total_candidates();
+    }
+}
+
+#[test]
+fn test_is_complete_unit() {
+    let mut unit: [Cell; 9] = [9, 8, 7, 6, 5, 4, 3, 2, 1].map(Cell::from);
+    assert!(is_complete_unit(&unit));
+
+    unit[4] = Cell::Unset;
+    assert!(unit.is_valid());
+    assert!(!is_complete_unit(&unit));
+
+    unit[4] = Cell::Nine;
+    assert!(!is_complete_unit(&unit));
+}
+
+#[test]
+fn test_unit_cells() {
+    let puzzle = crate::testing::sample();
+
+    let row = puzzle.row_cells(0);
+    assert!(row.iter().map(|&(i, _)| i).eq(0..9));
+    assert!(row.iter().map(|&(_, c)| c).eq(puzzle.row(0)));
+
+    for unit in 0..9 {
+        for (i, cell) in puzzle
+            .row_cells(unit)
+            .into_iter()
+            .chain(puzzle.column_cells(unit))
+            .chain(puzzle.block_cells(unit))
+        {
+            assert_eq!(puzzle.get_cell(i), cell);
+        }
+        assert!(
+            puzzle
+                .column_cells(unit)
+                .iter()
+                .map(|&(_, c)| c)
+                .eq(puzzle.column(unit))
+        );
+        assert!(
+            puzzle
+                .block_cells(unit)
+                .iter()
+                .map(|&(_, c)| c)
+                .eq(puzzle.block(unit))
+        );
+    }
+}
+
+#[test]
+fn test_validate_with() {
+    let puzzle = crate::testing::sample();
+
+    let mut messages = Vec::new();
+    assert!(
+        puzzle
+            .validate_with(|m| messages.push(m.to_string()))
+            .is_ok()
+    );
+    assert!(messages.is_empty());
+
+    // A second 5 in the first row and block
+    let mut messages = Vec::new();
+    assert!(matches!(
+        puzzle
+            .set_cell(2, Cell::Five)
+            .validate_with(|m| messages.push(m.to_string())),
+        Err(ValidationError::InvalidRow(0))
+    ));
+    assert_eq!(
+        messages,
+        vec![
+            "Row 0 of the puzzle is not valid!",
+            "Block 0 of the puzzle is not valid!"
+        ]
+    );
+}
+
+#[test]
+fn test_block_of_cell() {
+    use crate::geometry::block_of_cell;
+
+    let puzzle = crate::testing::sample();
+
+    for i in 0..81 {
+        let block = block_of_cell(i);
+        assert!(
+            puzzle
+                .block_cells(block)
+                .iter()
+                .any(|&(index, _)| index == i)
+        );
+        assert!(puzzle.block(block).contains(&puzzle.get_cell(i)));
+        assert!(
+            (0..81)
+                .filter(|&other| block_of_cell(other) == block && other != i)
+                .all(|other| peers(i).contains(&other))
+        );
+    }
+    assert_eq!(block_of_cell(80), 8);
+    assert_eq!(block_of_cell(33), 5);
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_arbitrary_puzzles_never_panic() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::{rng::SplitMix64, solver::solve_with_limit};
+
+    let mut rng = SplitMix64::new(1);
+    for _ in 0..500 {
+        // Short inputs leave most of the board unset, so a good share of the boards
+        // are valid and the solver gets past validation
+        let bytes: Vec<u8> = (0..rng.below(120)).map(|_| rng.next_u64() as u8).collect();
+        let mut u = Unstructured::new(&bytes);
+
+        let puzzle = Puzzle::arbitrary(&mut u).ok().unwrap();
+        if let Ok(solved) = solve_with_limit(&puzzle, 10_000) {
+            assert!(solved.is_solved());
+        }
+
+        let _ = String::from_utf8_lossy(&bytes).parse::<Puzzle>();
+    }
+}
+
+#[test]
+fn test_candidate_scores() {
+    // The 1 in cell 37 already rules out 1 for six of cell 0's peers: 1, 10 and 19 in
+    // its column, and 27, 36 and 45 in its block
+    let puzzle = Puzzle::empty().set_cell(37, Cell::One);
+
+    let scores = puzzle.candidate_scores(0);
+    assert_eq!(scores.len(), 9);
+    assert_eq!(scores[0], (Cell::One, 14));
+    assert!(scores[1..].iter().all(|&(_, affected)| affected == 20));
+    assert_eq!(scores[1].0, Cell::Two);
+
+    assert!(puzzle.candidate_scores(37).is_empty());
+
+    // A given among cell 0's peers isn't counted, so only the other 19 peers are left
+    let scores = Puzzle::empty().set_cell(1, Cell::Five).candidate_scores(0);
+    assert_eq!(scores.len(), 8);
+    assert!(
+        scores
+            .iter()
+            .all(|&(digit, affected)| digit != Cell::Five && affected == 19)
+    );
+}
+
+#[test]
+fn test_fingerprint() {
+    let solved = crate::testing::sample_solution();
+    let copy: Puzzle = solved.to_line().parse().ok().unwrap();
+
+    assert_eq!(solved.fingerprint(), copy.fingerprint());
+    assert_ne!(
+        solved.fingerprint(),
+        solved.set_cell(80, Cell::Unset).fingerprint()
+    );
+    assert_ne!(solved.fingerprint(), Puzzle::empty().fingerprint());
+
+    // The value is fixed, so stored fingerprints stay usable
+    assert_eq!(Puzzle::empty().fingerprint(), 0x0edb_e9ed_be9a_769f);
+}
+
+#[test]
+fn test_givens() {
+    let puzzle = crate::testing::sample();
+
+    let givens = puzzle.givens();
+    assert_eq!(givens.len(), 30);
+    assert_eq!(givens[0], ((0, 0), Cell::Five));
+    assert_eq!(givens[2], ((0, 4), Cell::Seven));
+    assert_eq!(givens[29], ((8, 8), Cell::Nine));
+    for ((row, column), cell) in givens {
+        assert_eq!(puzzle.get_cell(row * 9 + column), cell);
+    }
+
+    assert!(Puzzle::empty().givens().is_empty());
+}
+
+#[test]
+fn test_solution_only() {
+    let puzzle = crate::testing::sample();
+    let solution = crate::testing::sample_solution();
+
+    let complement = puzzle.solution_only(&solution);
+    assert_eq!(complement.get_cell(0), Cell::Unset);
+    assert_eq!(complement.get_cell(2), Cell::Four);
+    assert_eq!(complement.iter_set_cells().count(), 81 - 30);
+
+    // Every cell is set on exactly one of the two boards
+    let combined: [Cell; 81] =
+        std::array::from_fn(|i| match (puzzle.get_cell(i), complement.get_cell(i)) {
+            (Cell::Unset, cell) | (cell, Cell::Unset) => cell,
+            _ => panic!("cell {} is set on both boards", i),
+        });
+    assert_eq!(Puzzle::new(combined), solution);
+}
+
+#[test]
+fn test_digit_positions_in_unit() {
+    let puzzle = crate::testing::sample();
+
+    // Row 0 already has a 5, 3 and 7, so they can't go anywhere else in it
+    let row = puzzle.digit_positions_in_unit(Unit::Row(0));
+    assert_eq!(row[4], 0);
+    assert_eq!(row[2], 0);
+    assert_eq!(row[6], 0);
+    // 6 can only go in cells 3 and 5 of row 0, since the other columns and blocks
+    // already have one
+    assert_eq!(row[5], 0b101000);
+
+    for unit in (0..9).flat_map(|i| [Unit::Row(i), Unit::Column(i), Unit::Block(i)]) {
+        let positions = puzzle.digit_positions_in_unit(unit);
+        for (position, index) in unit.indexes().into_iter().enumerate() {
+            for digit in (1..=9).map(Cell::from) {
+                let possible = positions[u8::from(digit) as usize - 1] & (1 << position) != 0;
+                assert_eq!(
+                    possible,
+                    !puzzle.get_cell(index).is_set()
+                        && puzzle.possibilities(index).contains(&digit)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_try_place() {
+    let puzzle = crate::testing::sample();
+
+    let placed = puzzle.try_place(2, Cell::Four).ok().unwrap();
+    assert_eq!(placed, puzzle.set_cell(2, Cell::Four));
+
+    // Cell 2 shares row 0 with the 7 in cell 4
+    assert!(matches!(
+        puzzle.try_place(2, Cell::Seven),
+        Err(PlaceError::Conflict(4))
+    ));
+}
+
+#[test]
+fn test_block_major() {
+    let puzzle = crate::testing::sample();
+
+    let blocks = puzzle.to_block_major();
+    assert_eq!(blocks[..9], puzzle.block(0));
+    assert_eq!(blocks[36..45], puzzle.block(4));
+    assert_eq!(Puzzle::from_block_major(blocks), puzzle);
+
+    // Reading them as rows scrambles the board: the fourth cell of block 0 is cell 9
+    let swapped = Puzzle::new(blocks);
+    assert_ne!(swapped, puzzle);
+    assert_eq!(swapped.get_cell(0), puzzle.get_cell(0));
+    assert_eq!(swapped.get_cell(3), puzzle.get_cell(9));
+}
+
+#[test]
+fn test_option_grid() {
+    let puzzle = crate::testing::sample();
+
+    let grid = puzzle.to_option_grid();
+    assert_eq!(grid.len(), 9);
+    assert!(grid.iter().all(|row| row.len() == 9));
+    assert_eq!(grid[0][..3], [Some(5), Some(3), None]);
+    assert_eq!(grid[8][8], Some(9));
+    assert_eq!(Puzzle::from_option_grid(&grid).ok(), Some(puzzle));
+
+    assert!(matches!(
+        Puzzle::from_option_grid(&grid[..8]),
+        Err(PuzzleError::WrongLength(72))
+    ));
+    let mut short = grid.clone();
+    short[3].pop();
+    assert!(matches!(
+        Puzzle::from_option_grid(&short),
+        Err(PuzzleError::OnLine(3, _))
+    ));
+    let mut invalid = grid;
+    invalid[1][1] = Some(0);
+    assert!(matches!(
+        Puzzle::from_option_grid(&invalid),
+        Err(PuzzleError::InvalidValue(10, 0))
+    ));
+    invalid[1][1] = Some(10);
+    assert!(matches!(
+        Puzzle::from_option_grid(&invalid),
+        Err(PuzzleError::InvalidValue(10, 10))
+    ));
+}
+
+#[test]
+fn test_next_candidate() {
+    let puzzle = crate::testing::sample();
+
+    // Cell 2 can be 1, 2 or 4
+    assert_eq!(puzzle.next_candidate(2, Cell::Unset), Cell::One);
+    assert_eq!(puzzle.next_candidate(2, Cell::One), Cell::Two);
+    assert_eq!(puzzle.next_candidate(2, Cell::Two), Cell::Four);
+    assert_eq!(puzzle.next_candidate(2, Cell::Four), Cell::One);
+    // Illegal digits are skipped too
+    assert_eq!(puzzle.next_candidate(2, Cell::Three), Cell::Four);
+    assert_eq!(puzzle.next_candidate(2, Cell::Nine), Cell::One);
+
+    // The cell's own value doesn't count against it
+    let filled = puzzle.set_cell(2, Cell::Four);
+    assert_eq!(filled.next_candidate(2, Cell::Four), Cell::One);
+
+    // Cell 0 sees every digit but 1, so cycling only ever gives 1
+    let mut crowded = Puzzle::empty();
+    for (i, digit) in (2..=9).map(Cell::from).enumerate() {
+        crowded = crowded.set_cell(i + 1, digit);
+    }
+    assert_eq!(crowded.next_candidate(0, Cell::One), Cell::One);
+    assert_eq!(
+        crowded
+            .set_cell(9, Cell::One)
+            .next_candidate(0, Cell::Unset),
+        Cell::Unset
+    );
+}
+
+#[test]
+fn test_set_cells() {
+    let puzzle = crate::testing::sample();
+    let placements = [
+        (2, Cell::Four),
+        (3, Cell::Six),
+        (0, Cell::Unset),
+        (3, Cell::Two),
+    ];
+
+    let one_by_one = placements
+        .iter()
+        .fold(puzzle.clone(), |board, &(i, cell)| board.set_cell(i, cell));
+    assert_eq!(puzzle.set_cells(&placements), one_by_one);
+    assert_eq!(one_by_one.get_cell(3), Cell::Two);
+
+    assert_eq!(puzzle.set_cells(&[]), puzzle);
+}
+
+#[test]
+fn test_scramble() {
+    use crate::solver::count_solutions;
+
+    let puzzle = crate::testing::sample();
+    let canonical = puzzle.canonicalize();
+
+    for seed in 0..4 {
+        let scrambled = puzzle.scramble(seed);
+        assert_eq!(scrambled, puzzle.scramble(seed));
+        assert_ne!(scrambled, puzzle);
+        assert_eq!(scrambled.clue_count(), puzzle.clue_count());
+        assert_eq!(scrambled.canonicalize(), canonical);
+        assert_eq!(count_solutions(&scrambled, 2), 1);
+    }
+
+    assert_ne!(puzzle.scramble(0), puzzle.scramble(1));
+}
@@ -0,0 +1,244 @@
+//! Sudoku boards of other sizes, whose regions are rectangles instead of 3x3 squares.
+//! A board with regions `width` cells wide and `height` cells tall has `width * height`
+//! rows, columns and digits, so 2x3 regions make the popular 6x6 board.
+
+use crate::{
+    puzzle::{PuzzleError, ValidationError},
+    solver::SolveError,
+};
+
+/// The shape of the regions of a board, which also decides its size
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Shape {
+    width: usize,
+    height: usize,
+}
+
+impl Shape {
+    /// Regions `width` cells wide and `height` cells tall. Boards can have at most 16
+    /// digits.
+    pub fn new(width: usize, height: usize) -> Result<Self, PuzzleError> {
+        if width == 0 || height == 0 || width * height > 16 {
+            return Err(PuzzleError::InvalidShape(width, height));
+        }
+
+        Ok(Self { width, height })
+    }
+
+    /// The number of rows, columns, regions and digits
+    pub fn size(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// The row, column and region of the cell at `index`
+    pub fn coords(&self, index: usize) -> (usize, usize, usize) {
+        let (row, column) = (index / self.size(), index % self.size());
+        let regions_per_band = self.size() / self.width;
+        let region = (row / self.height) * regions_per_band + column / self.width;
+        (row, column, region)
+    }
+
+    /// The indexes of the cells that share a row, column or region with the cell at
+    /// `index`, not including the cell itself
+    fn peers(&self, index: usize) -> Vec<usize> {
+        let (row, column, region) = self.coords(index);
+        (0..self.size() * self.size())
+            .filter(|&i| i != index)
+            .filter(|&i| {
+                let (r, c, b) = self.coords(i);
+                r == row || c == column || b == region
+            })
+            .collect()
+    }
+}
+
+/// A board whose regions have a `Shape`. Cells hold 0 when unset, or a digit from 1 up
+/// to the size of the board.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RectangularPuzzle {
+    shape: Shape,
+    cells: Vec<u8>,
+}
+
+impl RectangularPuzzle {
+    /// Build a board from one value per cell, row by row
+    pub fn from_bytes(shape: Shape, bytes: &[u8]) -> Result<Self, PuzzleError> {
+        if bytes.len() != shape.size() * shape.size() {
+            return Err(PuzzleError::WrongLength(bytes.len()));
+        }
+        if let Some(i) = bytes.iter().position(|&b| b as usize > shape.size()) {
+            return Err(PuzzleError::InvalidValue(i, bytes[i]));
+        }
+
+        Ok(Self {
+            shape,
+            cells: bytes.to_vec(),
+        })
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// The value of the cell at `index`, which is 0 if it's unset
+    pub fn get_cell(&self, index: usize) -> u8 {
+        self.cells[index]
+    }
+
+    /// The digits that the cell at `index` could hold, in ascending order
+    pub fn possibilities(&self, index: usize) -> Vec<u8> {
+        values(self.candidates(index)).collect()
+    }
+
+    /// The candidates of a cell as a bitmask, with bit `n - 1` standing for digit `n`
+    fn candidates(&self, index: usize) -> u16 {
+        // Shifting a 1 past the top bit would overflow for boards with 16 digits
+        let all = u16::MAX >> (16 - self.shape.size());
+        self.shape
+            .peers(index)
+            .into_iter()
+            .filter(|&i| self.cells[i] != 0)
+            .fold(all, |mask, i| mask & !(1 << (self.cells[i] - 1)))
+    }
+
+    /// Whether every cell is set and no row, column or region has a digit twice
+    pub fn is_solved(&self) -> bool {
+        (0..self.cells.len()).all(|i| {
+            self.cells[i] != 0
+                && self
+                    .shape
+                    .peers(i)
+                    .into_iter()
+                    .all(|peer| self.cells[peer] != self.cells[i])
+        })
+    }
+
+    /// Check that no row, column or region has a digit twice
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        for i in (0..self.cells.len()).filter(|&i| self.cells[i] != 0) {
+            let (row, column, region) = self.shape.coords(i);
+
+            for peer in self.shape.peers(i) {
+                if self.cells[peer] != self.cells[i] {
+                    continue;
+                }
+
+                let (r, c, _) = self.shape.coords(peer);
+                return Err(if r == row {
+                    ValidationError::InvalidRow(row)
+                } else if c == column {
+                    ValidationError::InvalidColumn(column)
+                } else {
+                    ValidationError::InvalidBlock(region)
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterate over the digits in a candidate mask, which can go past 9 unlike
+/// `candidates::digits`
+fn values(mask: u16) -> impl Iterator<Item = u8> {
+    (1..=16u8).filter(move |digit| mask & 1 << (digit - 1) != 0)
+}
+
+/// Backtracking search, branching on the unset cell with the fewest candidates
+fn search(puzzle: &mut RectangularPuzzle) -> bool {
+    let Some(index) = (0..puzzle.cells.len())
+        .filter(|&i| puzzle.cells[i] == 0)
+        .min_by_key(|&i| puzzle.candidates(i).count_ones())
+    else {
+        return true;
+    };
+
+    for digit in values(puzzle.candidates(index)) {
+        puzzle.cells[index] = digit;
+        if search(puzzle) {
+            return true;
+        }
+    }
+
+    puzzle.cells[index] = 0;
+    false
+}
+
+/// Solve a board with rectangular regions
+pub fn solve_rectangular(puzzle: &RectangularPuzzle) -> Result<RectangularPuzzle, SolveError> {
+    puzzle.validate()?;
+
+    let mut solved = puzzle.clone();
+    if search(&mut solved) {
+        Ok(solved)
+    } else {
+        Err(SolveError::NoSolution)
+    }
+}
+
+#[test]
+fn test_shape() {
+    assert!(Shape::new(0, 3).is_err());
+    assert!(Shape::new(5, 4).is_err());
+
+    // 2 rows by 3 columns
+    let shape = Shape::new(3, 2).ok().unwrap();
+    assert_eq!(shape.size(), 6);
+    assert_eq!(shape.coords(0), (0, 0, 0));
+    assert_eq!(shape.coords(9), (1, 3, 1));
+    assert_eq!(shape.coords(14), (2, 2, 2));
+    assert_eq!(shape.coords(35), (5, 5, 5));
+    assert_eq!(shape.peers(0).len(), 5 + 5 + 2);
+}
+
+#[test]
+fn test_solve_6x6() {
+    let shape = Shape::new(3, 2).ok().unwrap();
+    #[rustfmt::skip]
+    let puzzle = RectangularPuzzle::from_bytes(shape, &[
+        1, 0, 3, 0, 5, 0,
+        0, 5, 0, 1, 0, 3,
+        2, 0, 4, 0, 6, 0,
+        0, 6, 0, 2, 0, 4,
+        3, 0, 5, 0, 1, 0,
+        0, 1, 0, 3, 0, 5,
+    ]).ok().unwrap();
+    assert_eq!(puzzle.possibilities(1), vec![2, 4]);
+
+    let solved = solve_rectangular(&puzzle).ok().unwrap();
+    assert!(solved.is_solved());
+    for i in (0..36).filter(|&i| puzzle.get_cell(i) != 0) {
+        assert_eq!(solved.get_cell(i), puzzle.get_cell(i));
+    }
+
+    assert!(matches!(
+        RectangularPuzzle::from_bytes(shape, &[7; 36]),
+        Err(PuzzleError::InvalidValue(0, 7))
+    ));
+    assert!(RectangularPuzzle::from_bytes(shape, &[0; 81]).is_err());
+
+    // Cells 0 and 7 are in the same region, but not the same row or column
+    let mut bytes = [0; 36];
+    bytes[0] = 4;
+    bytes[7] = 4;
+    let invalid = RectangularPuzzle::from_bytes(shape, &bytes).ok().unwrap();
+    assert!(matches!(
+        solve_rectangular(&invalid),
+        Err(SolveError::Invalid(ValidationError::InvalidBlock(0)))
+    ));
+}
+
+#[test]
+fn test_solve_larger() {
+    // 8x8 with 4x2 regions, 12x12 with 4x3 regions and 16x16 with 4x4 regions
+    for (width, height) in [(4, 2), (4, 3), (4, 4)] {
+        let shape = Shape::new(width, height).ok().unwrap();
+        let size = shape.size();
+        let empty = RectangularPuzzle::from_bytes(shape, &vec![0; size * size])
+            .ok()
+            .unwrap();
+
+        let solved = solve_rectangular(&empty).ok().unwrap();
+        assert!(solved.is_solved());
+    }
+}
@@ -0,0 +1,328 @@
+use std::fmt::Display;
+
+use crate::{candidates::Candidates, puzzle::Puzzle};
+
+/// Render a cell's value, with `.` for unset cells
+fn cell_char(puzzle: &Puzzle, index: usize) -> char {
+    match u8::from(puzzle.get_cell(index)) {
+        0 => '.',
+        value => (b'0' + value) as char,
+    }
+}
+
+/// Draw one horizontal line of the `to_unicode` grid. `fill` is the line itself and the
+/// other characters go where it meets the edges and the vertical lines.
+fn unicode_line(
+    left: char,
+    heavy_cross: char,
+    light_cross: char,
+    right: char,
+    fill: char,
+) -> String {
+    let mut out = String::new();
+    out.push(left);
+
+    for column in 0..9 {
+        out.extend([fill; 3]);
+        out.push(match column {
+            8 => right,
+            2 | 5 => heavy_cross,
+            _ => light_cross,
+        });
+    }
+
+    out.push('\n');
+    out
+}
+
+/// Render the board as plain ASCII, with `.` for unset cells and lines between the
+/// blocks
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..9 {
+            if row == 3 || row == 6 {
+                writeln!(f, "------+-------+------")?;
+            }
+
+            for column in 0..9 {
+                if column == 3 || column == 6 {
+                    write!(f, "| ")?;
+                }
+                write!(f, "{}", cell_char(self, row * 9 + column))?;
+                if column != 8 {
+                    write!(f, " ")?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Puzzle {
+    /// Render the board with Unicode box drawing characters. The outside border and the
+    /// lines between blocks are heavy, and the lines between cells within a block are
+    /// light.
+    pub fn to_unicode(&self) -> String {
+        let mut out = unicode_line('┏', '┳', '┯', '┓', '━');
+
+        for row in 0..9 {
+            out.push('┃');
+            for column in 0..9 {
+                out.push(' ');
+                out.push(cell_char(self, row * 9 + column));
+                out.push(' ');
+                out.push(if column % 3 == 2 { '┃' } else { '│' });
+            }
+            out.push('\n');
+
+            out.push_str(&match row {
+                8 => unicode_line('┗', '┻', '┷', '┛', '━'),
+                2 | 5 => unicode_line('┣', '╋', '┿', '┫', '━'),
+                _ => unicode_line('┠', '╂', '┼', '┨', '─'),
+            });
+        }
+
+        out
+    }
+
+    /// Render the board with pencil marks. Every cell is drawn as a 3x3 grid with room
+    /// for each of the digits 1-9, in the same layout as a phone keypad, and unset cells
+    /// show each of their candidates in its spot. Set cells show just their digit, in
+    /// the middle. Rows of cells are separated by blank lines.
+    pub fn to_pencilmark_grid(&self) -> String {
+        let candidates = Candidates::from_puzzle(self);
+        let mut out = String::new();
+
+        for row in 0..9 {
+            match row {
+                0 => (),
+                3 | 6 => out.push_str("------------+-------------+------------\n"),
+                _ => out.push('\n'),
+            }
+
+            for line in 0..3 {
+                let mut text = String::new();
+
+                for column in 0..9 {
+                    match column {
+                        0 => (),
+                        3 | 6 => text.push_str(" | "),
+                        _ => text.push(' '),
+                    }
+
+                    let index = row * 9 + column;
+                    let cell = self.get_cell(index);
+                    for spot in 0..3 {
+                        let digit = line * 3 + spot + 1;
+                        let shown = if cell.is_set() {
+                            line == 1 && spot == 1
+                        } else {
+                            candidates.mask(index) & 1 << (digit - 1) != 0
+                        };
+                        text.push(match (shown, cell.is_set()) {
+                            (false, _) => ' ',
+                            (true, true) => (b'0' + u8::from(cell)) as char,
+                            (true, false) => (b'0' + digit as u8) as char,
+                        });
+                    }
+                }
+
+                out.push_str(text.trim_end());
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+
+    /// Render the board for debugging. Set cells show their digit, and unset cells show
+    /// how many candidates they have left in parentheses, or `(X)` if they have none.
+    pub fn debug_candidates(&self) -> String {
+        let mut out = String::new();
+
+        for row in 0..9 {
+            if row == 3 || row == 6 {
+                out.push_str("---------+---------+---------\n");
+            }
+
+            for column in 0..9 {
+                if column == 3 || column == 6 {
+                    out.push('|');
+                }
+
+                let index = row * 9 + column;
+                let cell = self.get_cell(index);
+                let rendered = if cell.is_set() {
+                    format!(" {} ", u8::from(cell))
+                } else {
+                    match self.possibilities(index).len() {
+                        0 => "(X)".to_string(),
+                        count => format!("({})", count),
+                    }
+                };
+                out.push_str(&rendered);
+            }
+
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+/// The ANSI escape codes that `render_diff` puts around the cells the solver filled in
+const ADDED_COLOR: &str = "\x1b[32m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// Render `solved` in the same layout as the `Display` impl, showing which cells were
+/// filled in by the solver. With `color` those cells are green, and without it (for
+/// output that isn't going to a terminal) each of them is followed by a `*`.
+pub fn render_diff(original: &Puzzle, solved: &Puzzle, color: bool) -> String {
+    let mut out = String::new();
+
+    for row in 0..9 {
+        if row == 3 || row == 6 {
+            out.push_str("------+-------+------\n");
+        }
+
+        let mut line = String::new();
+        for column in 0..9 {
+            if column == 3 || column == 6 {
+                line.push_str("| ");
+            }
+
+            let index = row * 9 + column;
+            let added = !original.get_cell(index).is_set() && solved.get_cell(index).is_set();
+            let digit = cell_char(solved, index);
+            match (added, color) {
+                (true, true) => line.push_str(&format!("{}{}{} ", ADDED_COLOR, digit, RESET_COLOR)),
+                (true, false) => line.push_str(&format!("{}*", digit)),
+                (false, _) => line.push_str(&format!("{} ", digit)),
+            }
+        }
+
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    out
+}
+
+#[test]
+fn test_debug_candidates() {
+    use crate::puzzle::Cell;
+
+    // Cell 0 can't be anything: its row has 1-8 and its column has 9
+    let mut puzzle = Puzzle::empty();
+    for i in 1..9 {
+        puzzle = puzzle.set_cell(i, Cell::from(i as u8));
+    }
+    puzzle = puzzle.set_cell(9, Cell::Nine);
+
+    let rendered = puzzle.debug_candidates();
+    let lines: Vec<&str> = rendered.lines().collect();
+
+    assert_eq!(lines.len(), 11);
+    assert_eq!(lines[0], "(X) 1  2 | 3  4  5 | 6  7  8 ");
+    assert!(lines[1].starts_with(" 9 (6)(6)|"));
+    assert_eq!(rendered.matches("(X)").count(), 1);
+}
+
+#[test]
+fn test_display() {
+    let puzzle = crate::testing::sample();
+
+    let rendered = puzzle.to_string();
+    let lines: Vec<&str> = rendered.lines().collect();
+
+    assert_eq!(lines.len(), 11);
+    assert_eq!(lines[0], "5 3 . | . 7 . | . . .");
+    assert_eq!(lines[3], "------+-------+------");
+    assert_eq!(lines[10], ". . . | . 8 . | . 7 9");
+}
+
+#[test]
+fn test_to_unicode() {
+    let puzzle = crate::testing::sample();
+
+    let rendered = puzzle.to_unicode();
+    let lines: Vec<&str> = rendered.lines().collect();
+
+    assert_eq!(lines.len(), 19);
+    assert_eq!(lines[0], "┏━━━┯━━━┯━━━┳━━━┯━━━┯━━━┳━━━┯━━━┯━━━┓");
+    assert_eq!(lines[1], "┃ 5 │ 3 │ . ┃ . │ 7 │ . ┃ . │ . │ . ┃");
+    assert_eq!(lines[18], "┗━━━┷━━━┷━━━┻━━━┷━━━┷━━━┻━━━┷━━━┷━━━┛");
+
+    // Lines between blocks are heavy, and lines within blocks are light
+    for (i, line) in lines.iter().enumerate().filter(|(i, _)| i % 2 == 0) {
+        let heavy = i % 6 == 0;
+        assert_eq!(line.contains('━'), heavy);
+        assert_eq!(line.contains('─'), !heavy);
+    }
+    assert_eq!(lines[6], "┣━━━┿━━━┿━━━╋━━━┿━━━┿━━━╋━━━┿━━━┿━━━┫");
+    assert_eq!(lines[2], "┠───┼───┼───╂───┼───┼───╂───┼───┼───┨");
+}
+
+#[test]
+fn test_to_pencilmark_grid() {
+    use crate::puzzle::Cell;
+
+    // Cell 0 can only be 1, 5 or 9
+    let mut puzzle = Puzzle::empty();
+    for (i, digit) in [(3, 2), (4, 3), (5, 4), (27, 6), (36, 7), (45, 8)] {
+        puzzle = puzzle.set_cell(i, Cell::from(digit));
+    }
+
+    let rendered = puzzle.to_pencilmark_grid();
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines.len(), 9 * 3 + 8);
+
+    assert_eq!(&lines[0][..3], "1  ");
+    assert_eq!(&lines[1][..3], " 5 ");
+    assert_eq!(&lines[2][..3], "  9");
+
+    // Cell 3 holds a 2, in the middle of its grid
+    assert_eq!(&lines[0][14..17], "   ");
+    assert_eq!(&lines[1][14..17], " 2 ");
+    assert_eq!(&lines[2][14..17], "   ");
+
+    // The empty board's first cell has every candidate
+    let empty = Puzzle::empty().to_pencilmark_grid();
+    assert!(empty.starts_with("123 123 123 | 123 123 123 | 123 123 123\n"));
+    assert!(empty.contains("\n------------+-------------+------------\n"));
+}
+
+#[test]
+fn test_render_diff() {
+    let puzzle = crate::testing::sample();
+    let solved = crate::solver::solve_sudoku(&puzzle).ok().unwrap();
+
+    let plain = render_diff(&puzzle, &solved, false);
+    let lines: Vec<&str> = plain.lines().collect();
+    assert_eq!(lines[0], "5 3 4*| 6*7 8*| 9*1*2*");
+    assert_eq!(lines[3], "------+-------+------");
+    assert_eq!(plain.matches('*').count(), 81 - puzzle.clue_count());
+    assert!(!plain.contains('\x1b'));
+
+    // Without the markers it's just the solved board
+    assert_eq!(
+        plain
+            .replace("*|", " |")
+            .replace('*', " ")
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>(),
+        solved.to_string().lines().collect::<Vec<_>>()
+    );
+
+    let colored = render_diff(&puzzle, &solved, true);
+    assert!(colored.starts_with("5 3 \x1b[32m4\x1b[0m | "));
+    assert_eq!(
+        colored.matches(ADDED_COLOR).count(),
+        81 - puzzle.clue_count()
+    );
+    assert_eq!(render_diff(&solved, &solved, true), solved.to_string());
+}
@@ -1,25 +1,102 @@
+use std::cmp::Ordering;
+
 // type CompareFn<T> = dyn Fn(&T, &T) -> bool;
 
+/// Stable merge sort, where `compare(a, b)` says whether `a` can go before `b`. The
+/// sort works bottom up, merging sorted runs of 1, 2, 4... elements, so it doesn't
+/// recurse at all.
 pub fn merge_sort<T, F>(array: &[T], compare: F) -> Vec<T>
 where
     T: Clone,
-    F: Fn(&T, &T) -> bool + Clone,
+    F: Fn(&T, &T) -> bool,
 {
-    // Handle base case:
-    if array.len() == 1 {
-        return vec![array[0].clone()];
+    let mut sorted = array.to_vec();
+
+    let mut width = 1;
+    while width < sorted.len() {
+        let mut merged = Vec::with_capacity(sorted.len());
+        for run in sorted.chunks(2 * width) {
+            let (a, b) = run.split_at(width.min(run.len()));
+            merged.extend(merge(a, b, &compare));
+        }
+
+        sorted = merged;
+        width *= 2;
     }
 
-    // Split array in two
-    let split_index: usize = array.len() / 2;
-    let (a, b) = array.split_at(split_index);
+    sorted
+}
 
-    // Sort each half
-    let sorted_a = merge_sort(a, compare.clone());
-    let sorted_b = merge_sort(b, compare.clone());
+/// Like `merge_sort`, but with a comparison function that returns an `Ordering`. Equal
+/// elements keep their original order.
+pub fn merge_sort_by<T, F>(array: &[T], compare: F) -> Vec<T>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> Ordering,
+{
+    merge_sort(array, move |a, b| compare(a, b) != Ordering::Greater)
+}
 
-    // Merge the two halves
-    return merge(&sorted_a, &sorted_b, &compare);
+/// The indexes of `slice` in the order that would sort it. Only the indexes are moved
+/// around, so the elements don't need to be cloned. Equal elements keep their original
+/// order.
+pub fn argsort<T, F>(slice: &[T], compare: F) -> Vec<usize>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let indexes: Vec<usize> = (0..slice.len()).collect();
+    let compare = &compare;
+
+    merge_sort_by(&indexes, |a: &usize, b: &usize| {
+        compare(&slice[*a], &slice[*b])
+    })
+}
+
+/// Sort a slice in place with quicksort, without allocating. Elements equal to the pivot
+/// are grouped together at each step, so slices with many duplicates sort quickly.
+pub fn quicksort_in_place<T, F>(slice: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut slice = slice;
+
+    while slice.len() > 1 {
+        // Use the middle element as the pivot, kept at the start of the equal section
+        let middle = slice.len() / 2;
+        slice.swap(0, middle);
+
+        // Partition into [less than pivot | equal to pivot | greater than pivot]
+        let mut lt = 0;
+        let mut i = 1;
+        let mut gt = slice.len();
+        while i < gt {
+            match compare(&slice[i], &slice[lt]) {
+                Ordering::Less => {
+                    slice.swap(lt, i);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    gt -= 1;
+                    slice.swap(i, gt);
+                }
+                Ordering::Equal => i += 1,
+            }
+        }
+
+        let (rest, greater) = slice.split_at_mut(gt);
+        let less = &mut rest[..lt];
+
+        // Recurse into the smaller side and loop on the larger one, so the recursion
+        // depth stays logarithmic
+        if less.len() < greater.len() {
+            quicksort_in_place(less, compare);
+            slice = greater;
+        } else {
+            quicksort_in_place(greater, compare);
+            slice = less;
+        }
+    }
 }
 
 fn merge<T, F>(a: &[T], b: &[T], compare: &F) -> Vec<T>
@@ -59,3 +136,62 @@ where
 
     out
 }
+
+#[test]
+fn test_merge_sort() {
+    use crate::rng::SplitMix64;
+
+    let mut rng = SplitMix64::new(3);
+
+    for len in [0, 1, 2, 3, 5, 8, 100, 1000, 100_000] {
+        // Pairs of (key, original position), so that the order of equal keys shows
+        let input: Vec<(u32, usize)> = (0..len).map(|i| (rng.below(20) as u32, i)).collect();
+
+        let mut expected = input.clone();
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(merge_sort_by(&input, |a, b| a.0.cmp(&b.0)), expected);
+        assert_eq!(merge_sort(&input, |a, b| a.0 <= b.0), expected);
+    }
+}
+
+#[test]
+fn test_quicksort_in_place() {
+    use crate::rng::SplitMix64;
+
+    let mut rng = SplitMix64::new(7);
+
+    for len in [0, 1, 2, 3, 10, 100, 1000] {
+        // A small range of values makes sure there are plenty of duplicates
+        let input: Vec<u32> = (0..len).map(|_| rng.below(20) as u32).collect();
+
+        let expected = merge_sort_by(&input, |a: &u32, b: &u32| a.cmp(b));
+        let mut sorted = input.clone();
+        quicksort_in_place(&mut sorted, &|a: &u32, b: &u32| a.cmp(b));
+        assert_eq!(sorted, expected);
+
+        let expected = merge_sort_by(&input, |a: &u32, b: &u32| b.cmp(a));
+        let mut sorted = input;
+        quicksort_in_place(&mut sorted, &|a: &u32, b: &u32| b.cmp(a));
+        assert_eq!(sorted, expected);
+    }
+
+    let mut same = vec![4; 50];
+    quicksort_in_place(&mut same, &|a: &i32, b: &i32| a.cmp(b));
+    assert_eq!(same, vec![4; 50]);
+}
+
+#[test]
+fn test_argsort() {
+    let words = ["pear", "fig", "banana", "kiwi", "apple", "date"];
+    let order = argsort(&words, |a, b| a.len().cmp(&b.len()));
+
+    // Ties keep their original order
+    assert_eq!(order, vec![1, 0, 3, 5, 4, 2]);
+    let sorted: Vec<&str> = order.iter().map(|i| words[*i]).collect();
+    assert_eq!(
+        sorted,
+        vec!["fig", "pear", "kiwi", "date", "apple", "banana"]
+    );
+
+    assert!(argsort(&[] as &[u8], |a, b| a.cmp(b)).is_empty());
+}
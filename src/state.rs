@@ -0,0 +1,147 @@
+use std::{error::Error, fmt::Display};
+
+use crate::puzzle::{Cell, Puzzle, PuzzleError};
+
+#[derive(Debug)]
+pub enum EditError {
+    /// The cell at this index is one of the puzzle's givens, which can't be changed
+    Given(usize),
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use EditError::*;
+        match self {
+            Given(i) => write!(f, "Cell {} is a given and can't be changed", i),
+        }
+    }
+}
+
+impl Error for EditError {}
+
+/// A puzzle being played. Besides the board, it remembers which cells were givens, so
+/// that only the cells the player filled in can be changed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PuzzleState {
+    puzzle: Puzzle,
+    givens: [bool; 81],
+}
+
+impl PuzzleState {
+    /// Start playing a puzzle. Every set cell becomes a given.
+    pub fn new(puzzle: Puzzle) -> Self {
+        let givens = std::array::from_fn(|i| puzzle.get_cell(i).is_set());
+        Self { puzzle, givens }
+    }
+
+    /// The board as it currently is, including the player's placements
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    /// Whether the cell at `index` is one of the givens
+    pub fn is_given(&self, index: usize) -> bool {
+        self.givens[index]
+    }
+
+    /// Fill in a cell, or clear it by placing `Cell::Unset`. Givens can't be changed.
+    pub fn place(&mut self, index: usize, cell: Cell) -> Result<(), EditError> {
+        debug_assert!(index < 81);
+
+        if self.givens[index] {
+            return Err(EditError::Given(index));
+        }
+
+        self.puzzle = self.puzzle.set_cell(index, cell);
+        Ok(())
+    }
+
+    /// Write the game as a line of 81 characters that keeps track of which cells are
+    /// givens. Givens are written as the digits `1`-`9`, cells the player filled in as
+    /// the letters `a`-`i` (`a` for 1 up to `i` for 9), and blank cells as `.`.
+    pub fn to_markup(&self) -> String {
+        (0..81)
+            .map(|i| match u8::from(self.puzzle.get_cell(i)) {
+                0 => '.',
+                digit if self.givens[i] => (b'0' + digit) as char,
+                digit => (b'a' + digit - 1) as char,
+            })
+            .collect()
+    }
+
+    /// Read a game written by `to_markup`. `0` also works for blank cells, and the
+    /// letters can be uppercase.
+    pub fn from_markup(text: &str) -> Result<Self, PuzzleError> {
+        let mut values = Vec::with_capacity(81);
+        let mut givens = Vec::with_capacity(81);
+
+        for c in text.trim().chars() {
+            let (value, given) = match c.to_ascii_lowercase() {
+                '.' | '0' => (0, false),
+                '1'..='9' => (c as u8 - b'0', true),
+                'a'..='i' => (c.to_ascii_lowercase() as u8 - b'a' + 1, false),
+                _ => return Err(PuzzleError::InvalidCharacter(c)),
+            };
+            values.push(value);
+            givens.push(given);
+        }
+
+        let puzzle = Puzzle::from_bytes(&values)?;
+        Ok(Self {
+            puzzle,
+            givens: std::array::from_fn(|i| givens[i]),
+        })
+    }
+}
+
+#[test]
+fn test_place() {
+    let puzzle = crate::testing::sample();
+    let mut state = PuzzleState::new(puzzle.clone());
+
+    assert!(state.is_given(0));
+    assert!(!state.is_given(2));
+
+    // Givens can't be changed or cleared
+    assert!(matches!(
+        state.place(0, Cell::One),
+        Err(EditError::Given(0))
+    ));
+    assert!(matches!(
+        state.place(1, Cell::Unset),
+        Err(EditError::Given(1))
+    ));
+    assert_eq!(state.puzzle(), &puzzle);
+
+    // Blank cells can be filled in, changed and cleared again
+    assert!(state.place(2, Cell::Four).is_ok());
+    assert_eq!(state.puzzle().get_cell(2), Cell::Four);
+    assert!(!state.is_given(2));
+    assert!(state.place(2, Cell::Two).is_ok());
+    assert!(state.place(2, Cell::Unset).is_ok());
+    assert_eq!(state.puzzle(), &puzzle);
+}
+
+#[test]
+fn test_markup() {
+    let puzzle = crate::testing::sample();
+    let mut state = PuzzleState::new(puzzle);
+    assert!(state.place(2, Cell::Four).is_ok());
+    assert!(state.place(3, Cell::Nine).is_ok());
+
+    let markup = state.to_markup();
+    assert!(markup.starts_with("53di7...."));
+
+    let loaded = PuzzleState::from_markup(&markup).ok().unwrap();
+    assert_eq!(loaded, state);
+    assert!(loaded.is_given(0));
+    assert!(!loaded.is_given(2));
+    assert_eq!(loaded.puzzle().get_cell(3), Cell::Nine);
+
+    assert!(PuzzleState::from_markup(&markup.to_uppercase()).is_ok_and(|s| s == state));
+    assert!(matches!(
+        PuzzleState::from_markup(&markup.replacen('d', "j", 1)),
+        Err(PuzzleError::InvalidCharacter('j'))
+    ));
+    assert!(PuzzleState::from_markup("53di7").is_err());
+}
@@ -0,0 +1,77 @@
+//! Symmetries of the pattern of givens, which puzzle makers often aim for.
+
+use crate::puzzle::Puzzle;
+
+/// A way of mapping the board onto itself
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Symmetry {
+    /// No symmetry at all
+    None,
+    /// Turning the board 180 degrees
+    Rotational,
+    /// Mirroring the board left to right
+    Horizontal,
+    /// Mirroring the board top to bottom
+    Vertical,
+    /// Mirroring the board along the main diagonal
+    Diagonal,
+}
+
+impl Symmetry {
+    /// The symmetries that `clue_symmetry` checks, in the order it checks them
+    const CHECKED: [Symmetry; 4] = [
+        Symmetry::Rotational,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Diagonal,
+    ];
+
+    /// The index that the cell at `index` is mapped to
+    fn map(self, index: usize) -> usize {
+        let (row, column) = (index / 9, index % 9);
+        match self {
+            Symmetry::None => index,
+            Symmetry::Rotational => 80 - index,
+            Symmetry::Horizontal => row * 9 + (8 - column),
+            Symmetry::Vertical => (8 - row) * 9 + column,
+            Symmetry::Diagonal => column * 9 + row,
+        }
+    }
+}
+
+impl Puzzle {
+    /// Which symmetry the positions of the givens have, whatever their digits are. A
+    /// pattern can have more than one symmetry, in which case the first of rotational,
+    /// horizontal, vertical and diagonal that it has is returned.
+    pub fn clue_symmetry(&self) -> Symmetry {
+        Symmetry::CHECKED
+            .into_iter()
+            .find(|&symmetry| {
+                self.iter_set_cells()
+                    .all(|(i, _)| self.get_cell(symmetry.map(i)).is_set())
+            })
+            .unwrap_or(Symmetry::None)
+    }
+}
+
+#[test]
+fn test_clue_symmetry() {
+    use crate::{generator::generate_symmetric, puzzle::Cell};
+
+    assert_eq!(generate_symmetric(3).clue_symmetry(), Symmetry::Rotational);
+
+    // Every cell of the empty board is mapped to an unset cell
+    assert_eq!(Puzzle::empty().clue_symmetry(), Symmetry::Rotational);
+
+    // The middle of the top row is its own mirror image left to right
+    let top = Puzzle::empty().set_cell(4, Cell::One);
+    assert_eq!(top.clue_symmetry(), Symmetry::Horizontal);
+    let left = Puzzle::empty().set_cell(36, Cell::One);
+    assert_eq!(left.clue_symmetry(), Symmetry::Vertical);
+    let corner = Puzzle::empty().set_cell(0, Cell::One);
+    assert_eq!(corner.clue_symmetry(), Symmetry::Diagonal);
+    assert_eq!(
+        corner.set_cell(1, Cell::Two).clue_symmetry(),
+        Symmetry::None
+    );
+}
@@ -0,0 +1,939 @@
+use std::fmt::Display;
+
+use crate::{
+    candidates::{Candidates, digits},
+    geometry::{block_indexes, column_indexes, coords, peers, row_indexes, shares_unit},
+    puzzle::{Cell, Puzzle},
+    solver::SolveError,
+};
+
+/// A technique that removes candidates, returning whether it changed anything
+type Eliminate = fn(&mut Candidates) -> bool;
+
+/// A technique for removing candidates, for picking one to apply by name
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    LockedCandidates,
+    NakedTriple,
+    HiddenTriple,
+    XWing,
+    Swordfish,
+    XyWing,
+}
+
+impl Technique {
+    /// Every technique, easiest first, which is the order `solve_logical` tries them
+    pub const ALL: [Technique; 8] = [
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::LockedCandidates,
+        Technique::NakedTriple,
+        Technique::HiddenTriple,
+        Technique::XWing,
+        Technique::Swordfish,
+        Technique::XyWing,
+    ];
+
+    /// The function that applies this technique
+    fn eliminate(self) -> Eliminate {
+        match self {
+            Technique::NakedSingle => eliminate_naked_singles,
+            Technique::HiddenSingle => eliminate_hidden_singles,
+            Technique::LockedCandidates => eliminate_locked_candidates,
+            Technique::NakedTriple => eliminate_naked_triples,
+            Technique::HiddenTriple => eliminate_hidden_triples,
+            Technique::XWing => eliminate_x_wing,
+            Technique::Swordfish => eliminate_swordfish,
+            Technique::XyWing => eliminate_xy_wing,
+        }
+    }
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Technique::NakedSingle => "Naked single",
+            Technique::HiddenSingle => "Hidden single",
+            Technique::LockedCandidates => "Locked candidates",
+            Technique::NakedTriple => "Naked triple",
+            Technique::HiddenTriple => "Hidden triple",
+            Technique::XWing => "X-Wing",
+            Technique::Swordfish => "Swordfish",
+            Technique::XyWing => "XY-Wing",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Apply a single pass of `technique`, returning whether it removed any candidates
+pub fn apply_technique(candidates: &mut Candidates, technique: Technique) -> bool {
+    technique.eliminate()(candidates)
+}
+
+/// Fill in as much of a puzzle as possible without guessing, by applying every
+/// technique until none of them makes any more progress. Returns the resulting board
+/// and whether it is completely solved. Boards whose givens conflict are returned
+/// unchanged.
+pub fn solve_logical(puzzle: &Puzzle) -> (Puzzle, bool) {
+    if !puzzle.all_conflicts().is_empty() {
+        return (puzzle.clone(), false);
+    }
+
+    match propagate(puzzle) {
+        Some(candidates) => {
+            let solved = candidates.to_puzzle();
+            let complete = solved.is_complete();
+            (solved, complete)
+        }
+        None => (puzzle.clone(), false),
+    }
+}
+
+/// Solve a puzzle using only the techniques in this module, refusing puzzles that can't
+/// be finished without guessing. This is useful for checking that a puzzle is fair for
+/// a human to solve.
+pub fn solve_no_guess(puzzle: &Puzzle) -> Result<Puzzle, SolveError> {
+    puzzle.validate()?;
+
+    let solved = propagate(puzzle).ok_or(SolveError::NoSolution)?.to_puzzle();
+    if solved.is_complete() {
+        Ok(solved)
+    } else {
+        Err(SolveError::RequiresGuessing)
+    }
+}
+
+/// Solve a puzzle with every technique, only guessing when they all stop making
+/// progress, and never nesting more than `max_depth` guesses inside each other. Gives
+/// `SolveError::DepthExceeded` if every solution needs deeper guessing. A depth of 0 is
+/// the same as `solve_no_guess`, except for the error.
+pub fn solve_max_depth(puzzle: &Puzzle, max_depth: usize) -> Result<Puzzle, SolveError> {
+    puzzle.validate()?;
+
+    search_depth(Candidates::from_puzzle(puzzle), max_depth).map(|c| c.to_puzzle())
+}
+
+fn search_depth(candidates: Candidates, depth: usize) -> Result<Candidates, SolveError> {
+    let candidates = propagate_candidates(candidates).ok_or(SolveError::NoSolution)?;
+
+    // Guess on the cell with the fewest candidates
+    let Some(index) = (0..81)
+        .filter(|&i| candidates.mask(i).count_ones() > 1)
+        .min_by_key(|&i| candidates.mask(i).count_ones())
+    else {
+        return Ok(candidates);
+    };
+    if depth == 0 {
+        return Err(SolveError::DepthExceeded);
+    }
+
+    // Only give `NoSolution` if every guess is a dead end, not if some of them ran out
+    // of depth
+    let mut exceeded = false;
+    for digit in digits(candidates.mask(index)) {
+        let mut guess = candidates;
+        guess.set_mask(index, digit.mask());
+        match search_depth(guess, depth - 1) {
+            Ok(solved) => return Ok(solved),
+            Err(SolveError::DepthExceeded) => exceeded = true,
+            Err(_) => {}
+        }
+    }
+
+    Err(if exceeded {
+        SolveError::DepthExceeded
+    } else {
+        SolveError::NoSolution
+    })
+}
+
+/// Apply every technique until none of them makes any more progress. Returns `None` if
+/// a cell runs out of candidates, which means the puzzle has no solution.
+fn propagate(puzzle: &Puzzle) -> Option<Candidates> {
+    propagate_candidates(Candidates::from_puzzle(puzzle))
+}
+
+fn propagate_candidates(mut candidates: Candidates) -> Option<Candidates> {
+    while Technique::ALL
+        .iter()
+        .any(|&technique| apply_technique(&mut candidates, technique))
+    {}
+
+    // A cell without any candidates means the puzzle has no solution, and the singles
+    // next to it can't be trusted
+    if (0..81).any(|i| candidates.mask(i) == 0) {
+        None
+    } else {
+        Some(candidates)
+    }
+}
+
+impl Puzzle {
+    /// Fill in every cell that only has one candidate left, repeating until there are
+    /// none left. Nothing beyond naked singles is used, so cells that need any other
+    /// deduction (or a guess) stay unset.
+    pub fn fill_forced(&self) -> Puzzle {
+        self.fill_singles_by_round().0
+    }
+
+    /// How many rounds of naked singles the puzzle takes, where each round fills in every
+    /// cell that has only one candidate at the start of the round. Stops counting once
+    /// the board is complete or a round has nothing to fill in, so a puzzle that needs
+    /// other deductions only counts the rounds before it gets stuck.
+    pub fn naked_single_depth(&self) -> usize {
+        self.fill_singles_by_round().1
+    }
+
+    /// Fill in naked singles a round at a time until there are none left, returning the
+    /// filled board and the number of rounds. Boards whose givens conflict are returned
+    /// unchanged after 0 rounds.
+    fn fill_singles_by_round(&self) -> (Puzzle, usize) {
+        if !self.all_conflicts().is_empty() {
+            return (self.clone(), 0);
+        }
+
+        let mut cells: [Cell; 81] = std::array::from_fn(|i| self.get_cell(i));
+        let mut candidates = Candidates::from_puzzle(self);
+        let mut rounds = 0;
+
+        loop {
+            let singles: Vec<(usize, Cell)> = (0..81)
+                .filter(|&i| !cells[i].is_set() && candidates.mask(i).count_ones() == 1)
+                .map(|i| (i, Cell::from(candidates.mask(i).trailing_zeros() as u8 + 1)))
+                .collect();
+            if singles.is_empty() {
+                return (Puzzle::new(cells), rounds);
+            }
+
+            for (i, digit) in singles {
+                // Two singles of the same round can need the same digit in one unit, so
+                // only the first of them is filled in
+                if !candidates.contains(i, digit) {
+                    continue;
+                }
+                cells[i] = digit;
+                for &peer in peers(i) {
+                    candidates.remove(peer, digit);
+                }
+            }
+            rounds += 1;
+        }
+    }
+}
+
+/// Eliminate candidates using naked singles: a cell with only one candidate left must
+/// hold that digit, so it can be removed from all of the cell's peers. Returns whether
+/// any candidates were removed.
+pub fn eliminate_naked_singles(candidates: &mut Candidates) -> bool {
+    let mut changed = false;
+
+    for i in 0..81 {
+        let mask = candidates.mask(i);
+        if mask.count_ones() != 1 {
+            continue;
+        }
+
+        for &peer in peers(i) {
+            if candidates.mask(peer) & mask != 0 {
+                candidates.set_mask(peer, candidates.mask(peer) & !mask);
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+/// Eliminate candidates using hidden singles: if a digit is a candidate in only one
+/// cell of a row, column or block, that cell must hold it, so every other candidate
+/// can be removed from the cell. Returns whether any candidates were removed.
+pub fn eliminate_hidden_singles(candidates: &mut Candidates) -> bool {
+    let mut changed = false;
+
+    for unit in units() {
+        for digit in (1..=9).map(Cell::from) {
+            let mut cells = unit.into_iter().filter(|&i| candidates.contains(i, digit));
+
+            if let (Some(i), None) = (cells.next(), cells.next())
+                && candidates.mask(i) != digit.mask()
+            {
+                candidates.set_mask(i, digit.mask());
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+/// Eliminate candidates using naked triples: if three cells of a row, column or block
+/// only have three digits between them as candidates, those digits must go in those
+/// cells, so they can be removed from the rest of the unit. Returns whether any
+/// candidates were removed.
+pub fn eliminate_naked_triples(candidates: &mut Candidates) -> bool {
+    eliminate_naked_subsets(candidates, 3)
+}
+
+/// Eliminate candidates using hidden triples: if three digits can only go in the same
+/// three cells of a row, column or block, those cells must hold those digits, so every
+/// other candidate can be removed from them. Returns whether any candidates were
+/// removed.
+pub fn eliminate_hidden_triples(candidates: &mut Candidates) -> bool {
+    eliminate_hidden_subsets(candidates, 3)
+}
+
+/// Every row, column and block
+fn units() -> impl Iterator<Item = [usize; 9]> {
+    (0..9).flat_map(|i| [row_indexes(i), column_indexes(i), block_indexes(i)])
+}
+
+/// Eliminate naked subsets of `size` cells in every unit. Cells with a single candidate
+/// are left to the naked singles.
+fn eliminate_naked_subsets(candidates: &mut Candidates, size: u32) -> bool {
+    let mut changed = false;
+
+    for unit in units() {
+        // Every set of `size` positions in the unit, as a bitmask
+        for positions in (0..1u16 << 9).filter(|p| p.count_ones() == size) {
+            let cells: Vec<usize> = (0..9)
+                .filter(|p| positions & 1 << p != 0)
+                .map(|p| unit[p])
+                .collect();
+            if !cells
+                .iter()
+                .all(|&i| (2..=size).contains(&candidates.mask(i).count_ones()))
+            {
+                continue;
+            }
+
+            let digits = cells.iter().fold(0, |mask, &i| mask | candidates.mask(i));
+            if digits.count_ones() != size {
+                continue;
+            }
+
+            for &i in unit.iter().filter(|i| !cells.contains(i)) {
+                if candidates.mask(i) & digits != 0 {
+                    candidates.set_mask(i, candidates.mask(i) & !digits);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// Eliminate hidden subsets of `size` digits in every unit
+fn eliminate_hidden_subsets(candidates: &mut Candidates, size: u32) -> bool {
+    let mut changed = false;
+
+    for unit in units() {
+        // For each digit, a bitmask of the positions in the unit where it's a candidate
+        let positions: [u16; 9] = std::array::from_fn(|d| {
+            let digit = Cell::from(d as u8 + 1);
+            (0..9)
+                .filter(|&p| candidates.contains(unit[p], digit))
+                .fold(0, |mask, p| mask | 1 << p)
+        });
+
+        // Every set of `size` digits, as a candidate mask
+        for digits in (0..1u16 << 9).filter(|d| d.count_ones() == size) {
+            if !(0..9)
+                .filter(|d| digits & 1 << d != 0)
+                .all(|d| (2..=size).contains(&positions[d].count_ones()))
+            {
+                continue;
+            }
+
+            let cover = (0..9)
+                .filter(|d| digits & 1 << d != 0)
+                .fold(0, |mask, d| mask | positions[d]);
+            if cover.count_ones() != size {
+                continue;
+            }
+
+            for p in (0..9).filter(|p| cover & 1 << p != 0) {
+                let mask = candidates.mask(unit[p]);
+                if mask & !digits != 0 {
+                    candidates.set_mask(unit[p], mask & digits);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// Eliminate candidates using X-Wings: if a digit can only go in the same two columns of
+/// two different rows, one of those rows must hold it in each column, so it can be removed
+/// from every other cell in those two columns. The same applies with rows and columns
+/// swapped. Returns whether any candidates were removed.
+pub fn eliminate_x_wing(candidates: &mut Candidates) -> bool {
+    eliminate_fish(candidates, 2)
+}
+
+/// Eliminate candidates using Swordfish, the three line version of an X-Wing: if a digit
+/// can only go in three columns across three different rows (each row having at least
+/// two of those columns), it can be removed from every other cell in those three
+/// columns. The same applies with rows and columns swapped. Returns whether any
+/// candidates were removed.
+pub fn eliminate_swordfish(candidates: &mut Candidates) -> bool {
+    eliminate_fish(candidates, 3)
+}
+
+/// Eliminate fish of the given size (2 for X-Wings, 3 for Swordfish) for every digit, in
+/// both orientations
+fn eliminate_fish(candidates: &mut Candidates, size: u32) -> bool {
+    let mut changed = false;
+
+    for digit in (1..=9).map(Cell::from) {
+        // Rows as the base lines, columns as the cover lines
+        changed |= fish(candidates, digit, size, |line, position| {
+            line * 9 + position
+        });
+        // Columns as the base lines, rows as the cover lines
+        changed |= fish(candidates, digit, size, |line, position| {
+            position * 9 + line
+        });
+    }
+
+    changed
+}
+
+/// Find fish of one size for one digit. `index` maps a line number and a position along
+/// that line to a cell index, which lets the same code search both rows and columns.
+fn fish<F>(candidates: &mut Candidates, digit: Cell, size: u32, index: F) -> bool
+where
+    F: Fn(usize, usize) -> usize,
+{
+    // For each line, a bitmask of the positions where the digit is a candidate
+    let positions: [u16; 9] = std::array::from_fn(|line| {
+        (0..9)
+            .filter(|&position| candidates.contains(index(line, position), digit))
+            .fold(0, |mask, position| mask | 1 << position)
+    });
+
+    let mut changed = false;
+
+    // Every set of `size` base lines, as a bitmask of line numbers
+    for lines in (0..1u16 << 9).filter(|lines| lines.count_ones() == size) {
+        let base: Vec<usize> = (0..9).filter(|l| lines & 1 << l != 0).collect();
+        if !base
+            .iter()
+            .all(|&l| (2..=size).contains(&positions[l].count_ones()))
+        {
+            continue;
+        }
+
+        let cover = base.iter().fold(0, |mask, &l| mask | positions[l]);
+        if cover.count_ones() != size {
+            continue;
+        }
+
+        for position in (0..9).filter(|p| cover & 1 << p != 0) {
+            for line in (0..9).filter(|l| !base.contains(l)) {
+                changed |= candidates.remove(index(line, position), digit);
+            }
+        }
+    }
+
+    changed
+}
+
+/// Eliminate candidates using XY-Wings: a pivot cell with candidates {X, Y} that sees
+/// two pincer cells with candidates {X, Z} and {Y, Z}. Whichever digit the pivot takes,
+/// one of the pincers must be Z, so Z can be removed from every cell that sees both
+/// pincers. Returns whether any candidates were removed.
+pub fn eliminate_xy_wing(candidates: &mut Candidates) -> bool {
+    let mut changed = false;
+
+    for pivot in 0..81 {
+        let xy = candidates.mask(pivot);
+        if xy.count_ones() != 2 {
+            continue;
+        }
+
+        // Pincers share exactly one digit with the pivot
+        let pincers: Vec<usize> = peers(pivot)
+            .iter()
+            .copied()
+            .filter(|&i| {
+                let mask = candidates.mask(i);
+                mask.count_ones() == 2 && (mask & xy).count_ones() == 1
+            })
+            .collect();
+
+        for (n, &a) in pincers.iter().enumerate() {
+            for &b in &pincers[n + 1..] {
+                let (mask_a, mask_b) = (candidates.mask(a), candidates.mask(b));
+                let z = mask_a & !xy;
+                if mask_a & xy == mask_b & xy || mask_b & !xy != z {
+                    continue;
+                }
+                let z = Cell::from(z.trailing_zeros() as u8 + 1);
+
+                for i in (0..81).filter(|&i| i != a && i != b) {
+                    if shares_unit(i, a) && shares_unit(i, b) {
+                        changed |= candidates.remove(i, z);
+                    }
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// Eliminate locked candidates. If a digit's candidates within a block all fall on one
+/// row or column, the digit can be removed from the rest of that row or column
+/// (pointing). If a digit's candidates within a row or column all fall in one block, the
+/// digit can be removed from the rest of that block (claiming). Returns whether any
+/// candidates were removed.
+pub fn eliminate_locked_candidates(candidates: &mut Candidates) -> bool {
+    let mut changed = false;
+
+    for digit in (1..=9).map(Cell::from) {
+        // Pointing
+        for block in 0..9 {
+            let cells: Vec<usize> = block_indexes(block)
+                .into_iter()
+                .filter(|&i| candidates.contains(i, digit))
+                .collect();
+            let Some(&first) = cells.first() else {
+                continue;
+            };
+            let (row, column, _) = coords(first);
+
+            let lines = [
+                (cells.iter().all(|&i| coords(i).0 == row), row_indexes(row)),
+                (
+                    cells.iter().all(|&i| coords(i).1 == column),
+                    column_indexes(column),
+                ),
+            ];
+            for (_, line) in lines.into_iter().filter(|(locked, _)| *locked) {
+                for i in line.into_iter().filter(|&i| coords(i).2 != block) {
+                    changed |= candidates.remove(i, digit);
+                }
+            }
+        }
+
+        // Claiming
+        for line in (0..9).flat_map(|i| [row_indexes(i), column_indexes(i)]) {
+            let cells: Vec<usize> = line
+                .into_iter()
+                .filter(|&i| candidates.contains(i, digit))
+                .collect();
+            let Some(&first) = cells.first() else {
+                continue;
+            };
+            let block = coords(first).2;
+
+            if cells.iter().all(|&i| coords(i).2 == block) {
+                for i in block_indexes(block)
+                    .into_iter()
+                    .filter(|i| !line.contains(i))
+                {
+                    changed |= candidates.remove(i, digit);
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+#[test]
+fn test_solve_logical() {
+    // This puzzle only needs naked singles
+    let puzzle = crate::testing::sample();
+    let (solved, complete) = solve_logical(&puzzle);
+    assert!(complete);
+    assert!(solved.is_solved());
+    assert!(
+        puzzle
+            .diff(&solved)
+            .iter()
+            .all(|&(_, old, _)| old == Cell::Unset)
+    );
+
+    // This one can't be finished without guessing
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    let (partial, complete) = solve_logical(&hard);
+    assert!(!complete);
+    assert!(
+        partial
+            .iter_set_cells()
+            .all(|(i, cell)| !hard.get_cell(i).is_set() || hard.get_cell(i) == cell)
+    );
+    assert!(partial.all_conflicts().is_empty());
+}
+
+#[test]
+fn test_fill_forced() {
+    use crate::solver::solve_sudoku;
+
+    // Naked singles are enough for this puzzle
+    let puzzle = crate::testing::sample();
+    assert_eq!(puzzle.fill_forced(), solve_sudoku(&puzzle).ok().unwrap());
+
+    // But not for this one
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    let solution = solve_sudoku(&hard).ok().unwrap();
+    let filled = hard.fill_forced();
+
+    assert!(!filled.is_complete());
+    assert!(
+        filled
+            .iter_set_cells()
+            .all(|(i, cell)| solution.get_cell(i) == cell)
+    );
+    assert!(
+        filled
+            .iter_unset_cells()
+            .all(|(i, _)| filled.possibilities(i).len() > 1)
+    );
+}
+
+#[test]
+fn test_naked_single_depth() {
+    let solution = crate::testing::sample_solution();
+    assert_eq!(solution.naked_single_depth(), 0);
+
+    // With one cell missing from each row, every missing cell is a naked single straight
+    // away
+    let one_round = (0..9).fold(solution.clone(), |board, row| board.unset_cell(row * 10));
+    assert_eq!(one_round.naked_single_depth(), 1);
+
+    let puzzle = crate::testing::sample();
+    // Naked singles solve this one too, but each round opens up the next
+    assert!(puzzle.naked_single_depth() > 1);
+
+    // This one doesn't have a single naked single to start from
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    assert_eq!(hard.naked_single_depth(), 0);
+}
+
+#[test]
+fn test_naked_singles() {
+    use crate::candidates::ALL_DIGITS;
+
+    let mut candidates = Candidates::new([ALL_DIGITS; 81]);
+    candidates.set_mask(40, Cell::Five.mask());
+
+    assert!(eliminate_naked_singles(&mut candidates));
+    for i in 0..81 {
+        let expected = if i == 40 {
+            Cell::Five.mask()
+        } else if peers(i).contains(&40) {
+            ALL_DIGITS & !Cell::Five.mask()
+        } else {
+            ALL_DIGITS
+        };
+        assert_eq!(candidates.mask(i), expected);
+    }
+
+    // Nothing left to remove
+    assert!(!eliminate_naked_singles(&mut candidates));
+}
+
+#[test]
+fn test_hidden_singles() {
+    use crate::candidates::ALL_DIGITS;
+
+    // 3 can only go in cell 4 of the first row
+    let mut candidates = Candidates::new([ALL_DIGITS; 81]);
+    for i in row_indexes(0).into_iter().filter(|&i| i != 4) {
+        candidates.remove(i, Cell::Three);
+    }
+
+    assert!(eliminate_hidden_singles(&mut candidates));
+    assert_eq!(candidates.mask(4), Cell::Three.mask());
+    assert_eq!(candidates.mask(5), ALL_DIGITS & !Cell::Three.mask());
+    assert_eq!(candidates.mask(13), ALL_DIGITS);
+
+    assert!(!eliminate_hidden_singles(&mut candidates));
+}
+
+#[test]
+fn test_x_wing() {
+    use crate::candidates::ALL_DIGITS;
+
+    // Five can only go in columns 2 and 7 of rows 1 and 5
+    let mut masks = [ALL_DIGITS; 81];
+    for row in [1, 5] {
+        for column in (0..9).filter(|c| *c != 2 && *c != 7) {
+            masks[row * 9 + column] &= !Cell::Five.mask();
+        }
+    }
+    let mut candidates = Candidates::new(masks);
+
+    assert!(eliminate_x_wing(&mut candidates));
+    for row in 0..9 {
+        for column in 0..9 {
+            let expected = match (row, column) {
+                (1 | 5, 2 | 7) => true,
+                (1 | 5, _) | (_, 2 | 7) => false,
+                _ => true,
+            };
+            assert_eq!(candidates.contains(row * 9 + column, Cell::Five), expected);
+        }
+    }
+    // Nothing left to eliminate
+    assert!(!eliminate_x_wing(&mut candidates));
+
+    // The mirror case: three can only go in rows 0 and 8 of columns 3 and 4
+    let mut masks = [ALL_DIGITS; 81];
+    for column in [3, 4] {
+        for row in 1..8 {
+            masks[row * 9 + column] &= !Cell::Three.mask();
+        }
+    }
+    let mut candidates = Candidates::new(masks);
+
+    assert!(eliminate_x_wing(&mut candidates));
+    for row in 0..9 {
+        for column in 0..9 {
+            let expected = match (row, column) {
+                (0 | 8, 3 | 4) => true,
+                (0 | 8, _) | (_, 3 | 4) => false,
+                _ => true,
+            };
+            assert_eq!(candidates.contains(row * 9 + column, Cell::Three), expected);
+        }
+    }
+}
+
+#[test]
+fn test_swordfish() {
+    use crate::candidates::ALL_DIGITS;
+
+    // Seven can only go in columns {0, 4}, {4, 8} and {0, 8} of rows 0, 4 and 8
+    let mut masks = [ALL_DIGITS; 81];
+    for (row, columns) in [(0, [0, 4]), (4, [4, 8]), (8, [0, 8])] {
+        for column in (0..9).filter(|c| !columns.contains(c)) {
+            masks[row * 9 + column] &= !Cell::Seven.mask();
+        }
+    }
+    let mut candidates = Candidates::new(masks);
+
+    // No two of the rows share the same pair of columns, so there's no X-Wing
+    assert!(!eliminate_x_wing(&mut candidates.clone()));
+
+    assert!(eliminate_swordfish(&mut candidates));
+    for row in 0..9 {
+        for column in 0..9 {
+            let expected = if [0, 4, 8].contains(&row) {
+                masks[row * 9 + column]
+            } else if [0, 4, 8].contains(&column) {
+                ALL_DIGITS & !Cell::Seven.mask()
+            } else {
+                ALL_DIGITS
+            };
+            assert_eq!(candidates.mask(row * 9 + column), expected);
+        }
+    }
+
+    assert!(!eliminate_swordfish(&mut candidates));
+}
+
+#[test]
+fn test_locked_candidates() {
+    use crate::candidates::ALL_DIGITS;
+
+    // Pointing: in block 0, four can only go in row 0
+    let mut masks = [ALL_DIGITS; 81];
+    for i in [9, 10, 11, 18, 19, 20] {
+        masks[i] &= !Cell::Four.mask();
+    }
+    let mut candidates = Candidates::new(masks);
+
+    assert!(eliminate_locked_candidates(&mut candidates));
+    let without_four: Vec<usize> = (0..81)
+        .filter(|&i| !candidates.contains(i, Cell::Four))
+        .collect();
+    assert_eq!(without_four, vec![3, 4, 5, 6, 7, 8, 9, 10, 11, 18, 19, 20]);
+    assert!(!eliminate_locked_candidates(&mut candidates));
+
+    // Claiming: in row 4, seven can only go in block 4
+    let mut masks = [ALL_DIGITS; 81];
+    for i in [36, 37, 38, 42, 43, 44] {
+        masks[i] &= !Cell::Seven.mask();
+    }
+    let mut candidates = Candidates::new(masks);
+
+    assert!(eliminate_locked_candidates(&mut candidates));
+    let without_seven: Vec<usize> = (0..81)
+        .filter(|&i| !candidates.contains(i, Cell::Seven))
+        .collect();
+    assert_eq!(
+        without_seven,
+        vec![30, 31, 32, 36, 37, 38, 42, 43, 44, 48, 49, 50]
+    );
+}
+
+#[test]
+fn test_solve_no_guess() {
+    use crate::puzzle::ValidationError;
+
+    let easy = crate::testing::sample();
+    let solved = solve_no_guess(&easy).ok().unwrap();
+    assert_eq!(solved, crate::testing::sample_solution());
+
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    assert!(matches!(
+        solve_no_guess(&hard),
+        Err(SolveError::RequiresGuessing)
+    ));
+
+    assert!(matches!(
+        solve_no_guess(&easy.set_cell(2, Cell::Five)),
+        Err(SolveError::Invalid(ValidationError::InvalidRow(0)))
+    ));
+}
+
+#[test]
+fn test_solve_max_depth() {
+    let easy = crate::testing::sample();
+    assert_eq!(
+        solve_max_depth(&easy, 0).ok(),
+        crate::solver::solve_sudoku(&easy).ok()
+    );
+
+    let hard: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    assert!(matches!(
+        solve_max_depth(&hard, 0),
+        Err(SolveError::DepthExceeded)
+    ));
+    // Five nested guesses are enough for this puzzle, but four aren't
+    assert_eq!(
+        solve_max_depth(&hard, 5).ok().unwrap().to_line(),
+        "812753649943682175675491283154237896369845721287169534521974368438526917796318452"
+    );
+    assert!(matches!(
+        solve_max_depth(&hard, 4),
+        Err(SolveError::DepthExceeded)
+    ));
+
+    // A dead end at every guess is a contradiction, not a lack of depth
+    assert!(matches!(
+        solve_max_depth(&easy.set_cell(2, Cell::Two), 5),
+        Err(SolveError::NoSolution)
+    ));
+}
+
+#[test]
+fn test_xy_wing() {
+    use crate::candidates::ALL_DIGITS;
+
+    // Pivot {1, 2} in cell 0, with pincers {1, 3} in cell 4 and {2, 3} in cell 18
+    let mut masks = [ALL_DIGITS; 81];
+    masks[0] = Cell::One.mask() | Cell::Two.mask();
+    masks[4] = Cell::One.mask() | Cell::Three.mask();
+    masks[18] = Cell::Two.mask() | Cell::Three.mask();
+    let mut candidates = Candidates::new(masks);
+
+    assert!(eliminate_xy_wing(&mut candidates));
+    let without_three: Vec<usize> = (0..81)
+        .filter(|&i| !candidates.contains(i, Cell::Three))
+        .collect();
+    assert_eq!(without_three, vec![0, 1, 2, 21, 22, 23]);
+    assert!(!eliminate_xy_wing(&mut candidates));
+
+    // The pincers have to share the same third digit
+    masks[18] = Cell::Two.mask() | Cell::Four.mask();
+    assert!(!eliminate_xy_wing(&mut Candidates::new(masks)));
+}
+
+#[test]
+fn test_naked_triples() {
+    use crate::candidates::ALL_DIGITS;
+
+    // Cells 0, 4 and 8 of the first row only have 1, 2 and 3 between them, and they're
+    // all in different blocks
+    let one_to_three = Cell::One.mask() | Cell::Two.mask() | Cell::Three.mask();
+    let mut masks = [ALL_DIGITS; 81];
+    masks[0] = Cell::One.mask() | Cell::Two.mask();
+    masks[4] = Cell::Two.mask() | Cell::Three.mask();
+    masks[8] = Cell::One.mask() | Cell::Three.mask();
+    let mut candidates = Candidates::new(masks);
+
+    assert!(eliminate_naked_triples(&mut candidates));
+    for (i, &mask) in masks.iter().enumerate() {
+        let expected = match i {
+            1..=3 | 5..=7 => ALL_DIGITS & !one_to_three,
+            _ => mask,
+        };
+        assert_eq!(candidates.mask(i), expected);
+    }
+    assert!(!eliminate_naked_triples(&mut candidates));
+}
+
+#[test]
+fn test_hidden_triples() {
+    use crate::candidates::ALL_DIGITS;
+
+    // 4, 5 and 6 can only go in cells 0, 4 and 8 of the first row
+    let four_to_six = Cell::Four.mask() | Cell::Five.mask() | Cell::Six.mask();
+    let mut masks = [ALL_DIGITS; 81];
+    for i in [1, 2, 3, 5, 6, 7] {
+        masks[i] &= !four_to_six;
+    }
+    let mut candidates = Candidates::new(masks);
+
+    // Every cell still has more than three candidates, so this isn't a naked triple
+    assert!(!eliminate_naked_triples(&mut candidates.clone()));
+
+    assert!(eliminate_hidden_triples(&mut candidates));
+    for (i, &mask) in masks.iter().enumerate() {
+        let expected = match i {
+            0 | 4 | 8 => four_to_six,
+            _ => mask,
+        };
+        assert_eq!(candidates.mask(i), expected);
+    }
+    assert!(!eliminate_hidden_triples(&mut candidates));
+}
+
+#[test]
+fn test_apply_technique() {
+    let puzzle: Puzzle =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .ok()
+            .unwrap();
+    let mut applied = Candidates::from_puzzle(&puzzle);
+    let mut direct = applied;
+
+    loop {
+        let changed = apply_technique(&mut applied, Technique::NakedSingle);
+        assert_eq!(changed, eliminate_naked_singles(&mut direct));
+        assert_eq!(applied, direct);
+        if !changed {
+            break;
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Helpers shared by tests in different modules
+
+use crate::{
+    candidates::Candidates,
+    dlx::solve_dlx,
+    jigsaw::{Regions, solve_jigsaw},
+    puzzle::Puzzle,
+    rectangular::{RectangularPuzzle, Shape, solve_rectangular},
+    solver::{
+        BranchStrategy, CellSelection, DigitOrder, has_unique_solution, solve_from_candidates,
+        solve_parallel, solve_sudoku, solve_with_strategy,
+    },
+    variant::{Variant, solve_variant},
+    verify::verify_solution,
+};
+
+/// The puzzle most tests start from. Naked singles are enough to solve it.
+pub(crate) fn sample() -> Puzzle {
+    "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+        .parse()
+        .ok()
+        .unwrap()
+}
+
+/// The only solution of `sample`
+pub(crate) fn sample_solution() -> Puzzle {
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+        .parse()
+        .ok()
+        .unwrap()
+}
+
+/// Run every solver on `puzzle` and check that their answers are consistent. Every
+/// solution has to be valid and agree with the givens, and they all have to agree on
+/// whether there is a solution. Puzzles with a unique solution also have to get the
+/// same solution from every solver.
+pub(crate) fn solvers_agree(puzzle: &Puzzle) -> bool {
+    let rectangular = |puzzle: &Puzzle| {
+        let shape = Shape::new(3, 3).ok()?;
+        let board = RectangularPuzzle::from_bytes(shape, &puzzle.to_vec()).ok()?;
+        let solved = solve_rectangular(&board).ok()?;
+        Puzzle::from_bytes(&(0..81).map(|i| solved.get_cell(i)).collect::<Vec<u8>>()).ok()
+    };
+    let candidates = Candidates::from_puzzle(puzzle);
+
+    let solutions = [
+        solve_sudoku(puzzle).ok(),
+        solve_with_strategy(
+            puzzle,
+            BranchStrategy {
+                cells: CellSelection::FirstUnset,
+                digits: DigitOrder::Descending,
+            },
+        )
+        .ok(),
+        solve_with_strategy(
+            puzzle,
+            BranchStrategy {
+                digits: DigitOrder::Random(1),
+                ..BranchStrategy::default()
+            },
+        )
+        .ok(),
+        solve_with_strategy(
+            puzzle,
+            BranchStrategy {
+                cells: CellSelection::MostCompleteUnit,
+                ..BranchStrategy::default()
+            },
+        )
+        .ok(),
+        solve_parallel(puzzle).ok(),
+        // Candidates of conflicting givens would be wrong, so those are left to the others
+        puzzle
+            .all_conflicts()
+            .is_empty()
+            .then(|| solve_from_candidates(&candidates))
+            .flatten(),
+        solve_dlx(puzzle),
+        solve_jigsaw(puzzle, &Regions::blocks()).ok(),
+        solve_variant(puzzle, Variant::Standard).ok(),
+        rectangular(puzzle),
+    ];
+
+    let solvable = solutions[0].is_some();
+    if solutions
+        .iter()
+        .any(|solution| solution.is_some() != solvable)
+    {
+        return false;
+    }
+    if !solutions
+        .iter()
+        .flatten()
+        .all(|solution| verify_solution(puzzle, solution).is_ok())
+    {
+        return false;
+    }
+
+    !has_unique_solution(puzzle) || solutions.iter().all(|s| *s == solutions[0])
+}
+
+#[test]
+fn test_solvers_agree() {
+    use crate::{generator::generate_symmetric, puzzle::Cell};
+
+    let puzzle = sample();
+    assert!(solvers_agree(&puzzle));
+    assert!(solvers_agree(&generate_symmetric(3)));
+
+    // Boards without a solution, and with many
+    assert!(solvers_agree(&puzzle.set_cell(2, Cell::Five)));
+    assert!(solvers_agree(&Puzzle::empty()));
+}
@@ -0,0 +1,184 @@
+//! Sudoku variants that add extra rules on top of the normal row, column and block
+//! rules.
+
+use std::collections::HashSet;
+
+use crate::{
+    candidates::{ALL_DIGITS, digits},
+    geometry::{coords, peers},
+    puzzle::{Cell, Puzzle, ValidationError},
+    solver::SolveError,
+};
+
+/// A set of rules for a sudoku
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Variant {
+    /// Only the normal rules
+    #[default]
+    Standard,
+    /// Cells a chess knight's move apart can't have the same digit
+    AntiKnight,
+}
+
+/// The moves a chess knight can make, as (row, column) offsets
+const KNIGHT_MOVES: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+impl Variant {
+    /// The cells that can't share a digit with the cell at `index` because of this
+    /// variant's extra rule, in index order. The normal peers aren't included.
+    pub fn extra_peers(&self, index: usize) -> Vec<usize> {
+        match self {
+            Variant::Standard => Vec::new(),
+            Variant::AntiKnight => {
+                let (row, column, _) = coords(index);
+                let mut cells: Vec<usize> = KNIGHT_MOVES
+                    .iter()
+                    .filter_map(|&(dr, dc)| {
+                        let r = row.checked_add_signed(dr).filter(|&r| r < 9)?;
+                        let c = column.checked_add_signed(dc).filter(|&c| c < 9)?;
+                        Some(r * 9 + c)
+                    })
+                    .collect();
+                cells.sort_unstable();
+                cells
+            }
+        }
+    }
+
+    /// Every cell that can't share a digit with the cell at `index`, including the
+    /// normal peers
+    fn all_peers(&self, index: usize) -> Vec<usize> {
+        let mut cells = peers(index).to_vec();
+        // A knight's move never stays in the same row or column, but it can stay in the
+        // same block
+        for cell in self.extra_peers(index) {
+            if !cells.contains(&cell) {
+                cells.push(cell);
+            }
+        }
+        cells
+    }
+}
+
+impl Puzzle {
+    /// Like `possibilities`, but also following the extra rule of `variant`
+    pub fn variant_possibilities(&self, cell_index: usize, variant: Variant) -> HashSet<Cell> {
+        let mut set = self.possibilities(cell_index);
+        for cell in variant.extra_peers(cell_index) {
+            set.remove(&self.get_cell(cell));
+        }
+        set
+    }
+
+    /// Like `validate`, but also checking the extra rule of `variant`
+    pub fn validate_variant(&self, variant: Variant) -> Result<(), ValidationError> {
+        self.validate()?;
+
+        for (i, cell) in self.iter_set_cells() {
+            if let Some(other) = variant
+                .extra_peers(i)
+                .into_iter()
+                .find(|&other| other > i && self.get_cell(other) == cell)
+            {
+                return Err(ValidationError::VariantConflict(i, other));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Backtracking search over a board where every cell has an arbitrary list of peers
+fn search(cells: &mut [Cell; 81], peers: &[Vec<usize>]) -> bool {
+    let candidates = |cells: &[Cell; 81], i: usize| {
+        peers[i]
+            .iter()
+            .fold(ALL_DIGITS, |mask, &peer| mask & !cells[peer].mask())
+    };
+
+    // Branch on the unset cell with the fewest candidates
+    let Some(index) = (0..81)
+        .filter(|&i| !cells[i].is_set())
+        .min_by_key(|&i| candidates(cells, i).count_ones())
+    else {
+        return true;
+    };
+
+    for digit in digits(candidates(cells, index)) {
+        cells[index] = digit;
+        if search(cells, peers) {
+            return true;
+        }
+    }
+
+    cells[index] = Cell::Unset;
+    false
+}
+
+/// Solve a puzzle following the rules of `variant`
+pub fn solve_variant(puzzle: &Puzzle, variant: Variant) -> Result<Puzzle, SolveError> {
+    puzzle.validate_variant(variant)?;
+
+    let peers: Vec<Vec<usize>> = (0..81).map(|i| variant.all_peers(i)).collect();
+    let mut cells: [Cell; 81] = std::array::from_fn(|i| puzzle.get_cell(i));
+
+    if search(&mut cells, &peers) {
+        Ok(Puzzle::new(cells))
+    } else {
+        Err(SolveError::NoSolution)
+    }
+}
+
+#[test]
+fn test_extra_peers() {
+    assert!(Variant::Standard.extra_peers(40).is_empty());
+
+    // The center cell has all eight knight moves, but a corner only has two
+    assert_eq!(
+        Variant::AntiKnight.extra_peers(40),
+        vec![21, 23, 29, 33, 47, 51, 57, 59]
+    );
+    assert_eq!(Variant::AntiKnight.extra_peers(0), vec![11, 19]);
+    // No wrapping around the edges of the board
+    assert_eq!(Variant::AntiKnight.extra_peers(8), vec![15, 25]);
+}
+
+#[test]
+fn test_solve_anti_knight() {
+    let solved = solve_variant(&Puzzle::empty(), Variant::AntiKnight)
+        .ok()
+        .unwrap();
+    assert!(solved.is_solved());
+    assert!(solved.validate_variant(Variant::AntiKnight).is_ok());
+    for i in 0..81 {
+        for other in Variant::AntiKnight.extra_peers(i) {
+            assert_ne!(solved.get_cell(i), solved.get_cell(other));
+        }
+    }
+
+    // This puzzle's only normal solution has the same digit a knight's move apart
+    // somewhere, so it has no anti-knight solution
+    let puzzle = crate::testing::sample();
+    let normal = crate::solver::solve_sudoku(&puzzle).ok().unwrap();
+    assert!(matches!(
+        normal.validate_variant(Variant::AntiKnight),
+        Err(ValidationError::VariantConflict(_, _))
+    ));
+    // Cell 15 is a knight's move away from the 7 in cell 4
+    assert!(puzzle.possibilities(15).contains(&Cell::Seven));
+    assert!(
+        !puzzle
+            .variant_possibilities(15, Variant::AntiKnight)
+            .contains(&Cell::Seven)
+    );
+    assert!(solve_variant(&puzzle, Variant::AntiKnight).is_err());
+}
@@ -0,0 +1,107 @@
+use std::{error::Error, fmt::Display};
+
+use crate::puzzle::Puzzle;
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The solution has a different value than the puzzle's given at this index
+    ContradictsGiven(usize),
+    /// Two cells that share a row, column, or block hold the same digit
+    RuleViolation(usize, usize),
+    /// The solution still has unset cells
+    Incomplete,
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use VerifyError::*;
+        match self {
+            ContradictsGiven(i) => write!(f, "Cell {} does not match the puzzle's given", i),
+            RuleViolation(a, b) => write!(f, "Cells {} and {} conflict with each other", a, b),
+            Incomplete => write!(f, "The solution is not completely filled in"),
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+/// Check that `solution` is a complete, valid grid that agrees with every given in
+/// `puzzle`. Unlike `Puzzle::is_solved`, this knows about the original clues.
+pub fn verify_solution(puzzle: &Puzzle, solution: &Puzzle) -> Result<(), VerifyError> {
+    if solution.iter_unset_cells().next().is_some() {
+        return Err(VerifyError::Incomplete);
+    }
+
+    for i in 0..81 {
+        let given = puzzle.get_cell(i);
+        if given.is_set() && given != solution.get_cell(i) {
+            return Err(VerifyError::ContradictsGiven(i));
+        }
+    }
+
+    if let Some((a, b)) = solution.all_conflicts().first() {
+        return Err(VerifyError::RuleViolation(*a, *b));
+    }
+
+    Ok(())
+}
+
+impl Puzzle {
+    /// The indexes of the set cells whose values differ from `solution`, in ascending
+    /// order. Unlike `verify_solution`, this points out exactly which entries are wrong.
+    pub fn conflicts_with_solution(&self, solution: &Puzzle) -> Vec<usize> {
+        self.iter_set_cells()
+            .filter(|&(i, cell)| cell != solution.get_cell(i))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[test]
+fn test_verify_solution() {
+    use crate::puzzle::Cell;
+
+    let puzzle = crate::testing::sample();
+    let solution = crate::testing::sample_solution();
+
+    assert!(verify_solution(&puzzle, &solution).is_ok());
+
+    // Leaving a cell blank
+    let incomplete = solution.set_cell(2, Cell::Unset);
+    assert!(matches!(
+        verify_solution(&puzzle, &incomplete),
+        Err(VerifyError::Incomplete)
+    ));
+
+    // Changing a given (cell 0 is a given 5)
+    let contradicting = solution.set_cell(0, Cell::Four);
+    assert!(matches!(
+        verify_solution(&puzzle, &contradicting),
+        Err(VerifyError::ContradictsGiven(0))
+    ));
+
+    // Changing a non-given so that it duplicates the 3 in cell 1
+    let invalid = solution.set_cell(2, Cell::Three);
+    assert!(matches!(
+        verify_solution(&puzzle, &invalid),
+        Err(VerifyError::RuleViolation(1, 2))
+    ));
+}
+
+#[test]
+fn test_conflicts_with_solution() {
+    use crate::puzzle::Cell;
+
+    let puzzle = crate::testing::sample();
+    let solution = crate::testing::sample_solution();
+
+    assert!(puzzle.conflicts_with_solution(&solution).is_empty());
+
+    // One right entry and two wrong ones (cell 2 should be a 4 and cell 79 a 7)
+    let in_progress = puzzle
+        .set_cell(2, Cell::One)
+        .set_cell(3, Cell::Six)
+        .set_cell(80, Cell::Unset)
+        .set_cell(79, Cell::Two);
+    assert_eq!(in_progress.conflicts_with_solution(&solution), vec![2, 79]);
+}